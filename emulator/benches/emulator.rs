@@ -0,0 +1,140 @@
+//! Throughput of the interpreter, sprite drawing, screen clears and framebuffer conversions
+//!
+//! Run with `cargo bench`, and keep results to compare against over time with
+//! `cargo bench -- --save-baseline <name>` then `cargo bench -- --baseline <name>`.
+use chip8_emulator::{snapshot, Compatibility, Emulator};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+
+/** Ten seconds of emulated time */
+const CYCLES: u32 = Emulator::CYCLES_PER_FRAME * 600;
+
+const GAMES: [&str; 8] = [
+    "airplane", "brix", "cavern", "invaders", "pong1", "pong2", "tetris", "worm",
+];
+
+/** Draws a 15 row sprite over and over, moving it diagonally across the screen */
+const SPRITES: [u8; 31] = [
+    0xA2, 0x10, // LD I, 0x210
+    0xD0, 0x1F, // DRW V0, V1, 15
+    0x70, 0x08, // ADD V0, 8
+    0x71, 0x05, // ADD V1, 5
+    0x12, 0x02, // JP 0x202
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x3C, 0x7E, 0xFF, 0xDB, 0xFF, 0xE7, 0x7E, 0x3C, 0x18, 0x3C, 0x7E, 0xFF, 0x66, 0x24, 0x42,
+];
+
+/** Clears the screen in a tight loop */
+const CLEAR: [u8; 4] = [
+    0x00, 0xE0, // CLS
+    0x12, 0x00, // JP 0x200
+];
+
+/** Fills every pixel of the screen with 8x8 blocks and then clears it, changing the whole display each pass */
+const FILL: [u8; 40] = [
+    0x00, 0xE0, // CLS
+    0x60, 0x00, // LD V0, 0
+    0x61, 0x00, // LD V1, 0
+    0xA2, 0x20, // LD I, 0x220
+    0xD0, 0x18, // DRW V0, V1, 8
+    0x70, 0x08, // ADD V0, 8
+    0x30, 0x40, // SE V0, 64
+    0x12, 0x08, // JP 0x208
+    0x60, 0x00, // LD V0, 0
+    0x71, 0x08, // ADD V1, 8
+    0x31, 0x20, // SE V1, 32
+    0x12, 0x08, // JP 0x208
+    0x12, 0x00, // JP 0x200
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+
+fn emulator(rom: &[u8]) -> Emulator {
+    let mut emulator = Emulator::headless(Compatibility::New);
+    emulator.set_seed(1);
+    emulator.load_rom(rom.to_vec()).unwrap();
+
+    emulator
+}
+
+fn game(name: &str) -> Vec<u8> {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("../public/roms/{}.ch8", name));
+    fs::read(path).unwrap()
+}
+
+fn run(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run");
+    group.throughput(Throughput::Elements(CYCLES as u64));
+    let workloads = GAMES.iter().map(|&name| (name, game(name))).chain([
+        ("sprites", SPRITES.to_vec()),
+        ("clear", CLEAR.to_vec()),
+        ("fill", FILL.to_vec()),
+    ]);
+    for (name, rom) in workloads {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || emulator(&rom),
+                |mut emulator| emulator.run(black_box(CYCLES)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn cycle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cycle");
+    group.throughput(Throughput::Elements(CYCLES as u64));
+    for name in ["invaders", "tetris"] {
+        let rom = game(name);
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || emulator(&rom),
+                |mut emulator| {
+                    for _ in 0..CYCLES {
+                        emulator.cycle();
+                    }
+                    emulator
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn snapshot(c: &mut Criterion) {
+    let mut emulator = emulator(&game("invaders"));
+    emulator.run(CYCLES);
+    let framebuffer = emulator.framebuffer();
+    let pixels = framebuffer.to_bytes().len() as u64;
+
+    let mut group = c.benchmark_group("snapshot");
+    group.throughput(Throughput::Elements(pixels));
+    group.bench_function("to_png", |b| {
+        b.iter(|| snapshot::to_png(black_box(framebuffer), 1))
+    });
+    group.bench_function("to_half_blocks", |b| {
+        b.iter(|| snapshot::to_half_blocks(black_box(framebuffer)))
+    });
+    group.finish();
+}
+
+fn framebuffer(c: &mut Criterion) {
+    let mut emulator = emulator(&game("invaders"));
+    emulator.run(CYCLES);
+    let framebuffer = emulator.framebuffer();
+    let pixels = framebuffer.to_bytes().len() as u64;
+
+    let mut group = c.benchmark_group("framebuffer");
+    group.throughput(Throughput::Elements(pixels));
+    group.bench_function("to_rgba", |b| b.iter(|| black_box(framebuffer).to_rgba()));
+    group.bench_function("to_bytes", |b| b.iter(|| black_box(framebuffer).to_bytes()));
+    group.finish();
+}
+
+criterion_group!(benches, run, cycle, snapshot, framebuffer);
+criterion_main!(benches);
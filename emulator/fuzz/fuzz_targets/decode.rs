@@ -0,0 +1,12 @@
+#![no_main]
+
+use chip8_emulator::instruction::{decode, disassemble};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|memory: &[u8]| {
+    for (address, opcode, instruction) in disassemble(memory, 0, memory.len() / 2) {
+        assert_eq!(decode(opcode), instruction);
+        assert_eq!(address as usize % 2, 0);
+        let _ = (instruction.to_string(), instruction.pattern());
+    }
+});
@@ -0,0 +1,56 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use chip8_emulator::input::{InputEvent, InputScript};
+use chip8_emulator::{Compatibility, Emulator, Platform};
+use libfuzzer_sys::fuzz_target;
+
+/** Cycles each ROM runs for, enough to get through a few loops without slowing the fuzzer down */
+const MAX_CYCLES: u32 = 10_000;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    original: bool,
+    platform: u8,
+    seed: u64,
+    /** Cycle and key of each press, `None` releases the key */
+    events: Vec<(u16, Option<u8>)>,
+    rom: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let compatibility = match input.original {
+        true => Compatibility::Original,
+        false => Compatibility::New,
+    };
+    let mut emulator = Emulator::headless(compatibility);
+    emulator.set_platform(match input.platform % 3 {
+        0 => Platform::Chip8,
+        1 => Platform::Chip8Hires,
+        _ => Platform::Eti660,
+    });
+    emulator.set_seed(input.seed);
+    if emulator.load_rom(input.rom).is_err() {
+        return;
+    }
+    let events = input.events.iter().map(|&(cycle, key)| InputEvent {
+        cycle: cycle as u64,
+        key: key.map(|key| key & 0xF),
+    });
+    let inputs = InputScript::init(events.collect());
+
+    for _ in 0..MAX_CYCLES {
+        emulator.apply_inputs(&inputs);
+        if emulator.run(1) == 0 {
+            break;
+        }
+    }
+
+    // Faults have to come out as faults, and the debugger views have to cope with wherever PC and I ended up
+    let state = emulator.state();
+    assert_eq!(state.halted, emulator.fault().is_some());
+    let _ = (
+        emulator.disassemble(state.pc, 16),
+        emulator.hexdump(state.i, 64),
+    );
+});
@@ -0,0 +1,475 @@
+// Native only, ratatui doesn't build for wasm32 and `wasm-pack test` builds every binary for it
+#![cfg_attr(target_arch = "wasm32", no_main)]
+#![cfg(not(target_arch = "wasm32"))]
+
+use chip8_emulator::{snapshot, Compatibility, Emulator, Platform};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant};
+use std::{env, fs, process, thread};
+
+const USAGE: &str = "\
+Debugs a CHIP-8 program in a full-screen terminal UI.
+
+Usage:
+  chip8-debug <rom> [--quirks original|new] [--platform chip8|chip8-hires|eti660] [--seed N]
+              [--symbols FILE]
+
+Keys:
+  F5 continue/pause   F9 toggle breakpoint at cursor   F10 step over   F11 step
+  Up/Down move the cursor   PgUp/PgDn scroll memory   : command   Esc quit
+  Other letters and digits go to the program like in the browser (1234/QWER/ASDF/ZXCV).
+
+Commands:
+  b <address> [if <condition>]   break, e.g. `b 2A4 if v3 == 0x10 && i > 0x300`
+  log <address> <message>        logpoint, e.g. `log draw V0 is {v0}, I is {i:x}`
+  d <address>                    delete a breakpoint
+  m <address>                    show memory from the address
+  reset                          reload the ROM
+Addresses are hex, or labels and file:line if symbols are loaded.
+Labels win over hex, so `face` is a label if there is one, write `0xface` for the number.";
+
+/** Frames a key stays down after a press, terminals usually don't report releases */
+const HOLD_FRAMES: u32 = 30;
+/** Cycles `step over` runs before giving up on a subroutine returning */
+const STEP_OVER_LIMIT: u32 = 1_000_000;
+const LOG_LINES: usize = 100;
+const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+struct Options {
+    rom: String,
+    compatibility: Compatibility,
+    platform: Platform,
+    seed: Option<u64>,
+    symbols: Option<String>,
+}
+
+struct Debugger {
+    emulator: Emulator,
+    running: bool,
+    /** Instruction the disassembly is centered on and F9 toggles a breakpoint at */
+    cursor: u16,
+    memory_address: u16,
+    /** Frames until the program's key gets released */
+    held_key: Option<u32>,
+    /** Command being typed after `:` */
+    prompt: Option<String>,
+    message: String,
+    /** Logpoint output, oldest first */
+    log: VecDeque<String>,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(message) = parse_options(&args).and_then(|options| debug(&options)) {
+        eprintln!("{}", message);
+        process::exit(2);
+    }
+}
+
+fn debug(options: &Options) -> Result<(), String> {
+    let rom =
+        fs::read(&options.rom).map_err(|e| format!("Failed reading {}: {}", options.rom, e))?;
+    let mut emulator = Emulator::headless(options.compatibility);
+    emulator.set_platform(options.platform);
+    if let Some(seed) = options.seed {
+        emulator.set_seed(seed);
+    }
+    emulator.load_rom(rom).map_err(|e| e.to_string())?;
+    if let Some(path) = &options.symbols {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("Failed reading {}: {}", path, e))?;
+        emulator.load_symbols(&text).map_err(|e| e.to_string())?;
+    }
+
+    let pc = emulator.state().pc;
+    let mut debugger = Debugger {
+        emulator,
+        running: false,
+        cursor: pc,
+        memory_address: pc & 0xFF0,
+        held_key: None,
+        prompt: None,
+        message: "Paused, F5 to continue".to_string(),
+        log: VecDeque::new(),
+    };
+
+    let mut terminal = ratatui::init();
+    let result = debugger.run(&mut terminal);
+    ratatui::restore();
+
+    result.map_err(|e| e.to_string())
+}
+
+impl Debugger {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            let frame_start = Instant::now();
+
+            while event::poll(Duration::ZERO)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Release && !self.handle_key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+            self.release_held_key();
+
+            if self.running {
+                self.emulator.run(Emulator::CYCLES_PER_FRAME);
+                self.cursor = self.emulator.state().pc;
+                self.check_stopped();
+            }
+            self.log.extend(self.emulator.logpoint_output());
+            if self.log.len() > LOG_LINES {
+                self.log.drain(..self.log.len() - LOG_LINES);
+            }
+
+            terminal.draw(|frame| self.render(frame))?;
+            thread::sleep(FRAME.saturating_sub(frame_start.elapsed()));
+        }
+    }
+
+    /** Returns false when the user quits */
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if let Some(prompt) = &mut self.prompt {
+            match key.code {
+                KeyCode::Char(c) => prompt.push(c),
+                KeyCode::Backspace => {
+                    prompt.pop();
+                }
+                KeyCode::Enter => {
+                    let command = prompt.clone();
+                    self.prompt = None;
+                    self.message = match self.execute(&command) {
+                        Ok(message) => message,
+                        Err(message) => format!("Error: {}", message),
+                    };
+                }
+                KeyCode::Esc => self.prompt = None,
+                _ => {}
+            }
+            return true;
+        }
+
+        let ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return false,
+            _ if ctrl_c => return false,
+            KeyCode::F(5) if self.running => self.stop("Paused"),
+            KeyCode::F(5) => {
+                self.emulator.resume();
+                self.running = true;
+                self.message = "Running, F5 to pause".to_string();
+            }
+            KeyCode::F(9) => self.toggle_breakpoint(self.cursor),
+            KeyCode::F(10) if !self.running => {
+                self.emulator.step_over(STEP_OVER_LIMIT);
+                if !self.check_stopped() {
+                    self.stop("Stepped over");
+                }
+            }
+            KeyCode::F(11) if !self.running => {
+                self.emulator.step();
+                if !self.check_stopped() {
+                    self.stop("Stepped");
+                }
+            }
+            KeyCode::Up => self.cursor = self.cursor.saturating_sub(2),
+            KeyCode::Down => self.cursor = (self.cursor + 2).min(0xFFE),
+            KeyCode::PageUp => self.memory_address = self.memory_address.saturating_sub(0x80),
+            KeyCode::PageDown => self.memory_address = (self.memory_address + 0x80).min(0xF80),
+            KeyCode::Char(':') => self.prompt = Some(String::new()),
+            KeyCode::Char(c) => self.press_key(c),
+            _ => {}
+        }
+        true
+    }
+
+    /** Sends a key to the program the same way the browser does, through its `KeyboardEvent.code` */
+    fn press_key(&mut self, c: char) {
+        let code = match c.to_ascii_uppercase() {
+            digit @ '0'..='9' => format!("Digit{}", digit),
+            letter @ 'A'..='Z' => format!("Key{}", letter),
+            _ => return,
+        };
+        self.emulator.on_key_down(&code);
+        self.held_key = Some(HOLD_FRAMES);
+    }
+    fn release_held_key(&mut self) {
+        self.held_key = match self.held_key {
+            Some(0) => {
+                self.emulator.on_key_up();
+                None
+            }
+            frames => frames.map(|frames| frames - 1),
+        };
+    }
+
+    /** Pauses if the last run hit a breakpoint or a fault, returns whether it did */
+    fn check_stopped(&mut self) -> bool {
+        let state = self.emulator.state();
+        if let Some(fault) = state.fault {
+            self.stop(&format!("Halted: {}", fault));
+        } else if let Some(address) = self.emulator.hit_breakpoint() {
+            self.stop(&format!(
+                "Breakpoint at {}",
+                self.emulator.describe_address(address)
+            ));
+        } else {
+            return false;
+        }
+        true
+    }
+    fn stop(&mut self, message: &str) {
+        self.running = false;
+        self.cursor = self.emulator.state().pc;
+        self.message = message.to_string();
+    }
+    fn toggle_breakpoint(&mut self, address: u16) {
+        if self.emulator.has_breakpoint(address) {
+            self.emulator.remove_breakpoint(address);
+        } else {
+            self.emulator.add_breakpoint(address);
+        }
+    }
+
+    fn execute(&mut self, command: &str) -> Result<String, String> {
+        let (name, arguments) = command
+            .trim()
+            .split_once(' ')
+            .unwrap_or((command.trim(), ""));
+        let (address, rest) = arguments
+            .trim()
+            .split_once(' ')
+            .unwrap_or((arguments.trim(), ""));
+
+        match name {
+            "b" => {
+                let address = self.resolve(address)?;
+                match rest.trim().strip_prefix("if ") {
+                    Some(condition) => self
+                        .emulator
+                        .add_conditional_breakpoint(address, condition)
+                        .map_err(|e| e.to_string())?,
+                    None if rest.trim().is_empty() => self.emulator.add_breakpoint(address),
+                    None => return Err("Expected `if <condition>` after the address".to_string()),
+                }
+                Ok(format!(
+                    "Breakpoint at {}",
+                    self.emulator.describe_address(address)
+                ))
+            }
+            "log" => {
+                let address = self.resolve(address)?;
+                self.emulator
+                    .add_logpoint(address, rest.trim(), None)
+                    .map_err(|e| e.to_string())?;
+                Ok(format!(
+                    "Logpoint at {}",
+                    self.emulator.describe_address(address)
+                ))
+            }
+            "d" => {
+                let address = self.resolve(address)?;
+                self.emulator.remove_breakpoint(address);
+                Ok(format!("Deleted breakpoint at {:#06X}", address))
+            }
+            "m" => {
+                self.memory_address = self.resolve(address)? & 0xFF0;
+                Ok(format!("Memory from {:#06X}", self.memory_address))
+            }
+            "reset" => {
+                self.emulator.reset_hard().map_err(|e| e.to_string())?;
+                self.stop("Reset");
+                Ok(self.message.clone())
+            }
+            _ => Err(format!("Unknown command {}", name)),
+        }
+    }
+    fn resolve(&self, address: &str) -> Result<u16, String> {
+        let number = |hex: &str| {
+            u16::from_str_radix(hex, 16)
+                .ok()
+                .filter(|&address| address <= 0xFFF)
+        };
+        let resolved = match address
+            .strip_prefix("0x")
+            .or_else(|| address.strip_prefix("0X"))
+        {
+            Some(hex) => number(hex),
+            None => self
+                .emulator
+                .resolve_address(address)
+                .or_else(|| number(address)),
+        };
+        resolved.ok_or_else(|| format!("Unknown address {}", address))
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [top, middle, memory, status] = Layout::vertical([
+            Constraint::Length(18),
+            Constraint::Min(8),
+            Constraint::Length(10),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+        let [disassembly, screen] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(66)]).areas(top);
+        let [registers, breakpoints] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(middle);
+
+        self.render_disassembly(frame, disassembly);
+        let screen_lines: Vec<Line> = snapshot::to_half_blocks(self.emulator.framebuffer())
+            .into_iter()
+            .map(Line::from)
+            .collect();
+        frame.render_widget(
+            Paragraph::new(screen_lines).block(Block::bordered().title("Screen")),
+            screen,
+        );
+
+        let mut register_lines: Vec<Line> = self
+            .emulator
+            .state()
+            .to_string()
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        register_lines.push(Line::from("Call stack:".bold()));
+        register_lines.extend(self.emulator.call_stack().into_iter().map(Line::from));
+        frame.render_widget(
+            Paragraph::new(register_lines).block(Block::bordered().title("Registers")),
+            registers,
+        );
+
+        let mut breakpoint_lines: Vec<Line> = self
+            .emulator
+            .breakpoints()
+            .into_iter()
+            .map(Line::from)
+            .collect();
+        breakpoint_lines.push(Line::from("Log:".bold()));
+        let visible = (breakpoints.height as usize).saturating_sub(breakpoint_lines.len() + 2);
+        let skipped = self.log.len().saturating_sub(visible);
+        breakpoint_lines.extend(
+            self.log
+                .iter()
+                .skip(skipped)
+                .map(|line| Line::from(line.as_str())),
+        );
+        frame.render_widget(
+            Paragraph::new(breakpoint_lines).block(Block::bordered().title("Breakpoints")),
+            breakpoints,
+        );
+
+        let length = (memory.height as usize).saturating_sub(2) * 16;
+        let memory_lines: Vec<Line> = self
+            .emulator
+            .hexdump(self.memory_address, length)
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(memory_lines).block(Block::bordered().title("Memory")),
+            memory,
+        );
+
+        let prompt = match &self.prompt {
+            Some(command) => format!(":{}", command),
+            None => {
+                "F5 continue/pause  F9 breakpoint  F10 step over  F11 step  : command  Esc quit"
+                    .to_string()
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(self.message.as_str().bold()),
+                Line::from(prompt),
+            ]),
+            status,
+        );
+    }
+    fn render_disassembly(&self, frame: &mut Frame, area: Rect) {
+        let rows = area.height.saturating_sub(2);
+        // Start an even number of bytes before the cursor so instructions line up with it
+        let start = self.cursor.saturating_sub(rows / 3 * 2) & !1 | self.cursor & 1;
+        let pc = self.emulator.state().pc;
+
+        let lines: Vec<Line> = self
+            .emulator
+            .disassemble(start, rows as usize)
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let address = start + i as u16 * 2;
+                let breakpoint = if self.emulator.has_breakpoint(address) {
+                    '●'
+                } else {
+                    ' '
+                };
+                let marker = if address == pc { '>' } else { ' ' };
+                let line = format!("{}{} {}", breakpoint, marker, text);
+                match address == self.cursor {
+                    true => Line::styled(line, Style::new().reversed()),
+                    false => Line::from(line),
+                }
+            })
+            .collect();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Disassembly")),
+            area,
+        );
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        rom: String::new(),
+        compatibility: Compatibility::Original,
+        platform: Platform::Chip8,
+        seed: None,
+        symbols: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.rom = arg.clone();
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--seed" => {
+                let seed = value
+                    .parse()
+                    .map_err(|_| format!("Invalid number for {}: {}", arg, value))?;
+                options.seed = Some(seed);
+            }
+            "--quirks" => {
+                options.compatibility = value.parse::<Compatibility>().map_err(|e| e.to_string())?
+            }
+            "--platform" => {
+                options.platform = value.parse::<Platform>().map_err(|e| e.to_string())?
+            }
+            "--symbols" => options.symbols = Some(value.clone()),
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    if options.rom.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
@@ -0,0 +1,139 @@
+use chip8_emulator::compare::{compare_runs, compare_traces, Divergence};
+use chip8_emulator::input::InputScript;
+use chip8_emulator::{trace, Compatibility, Emulator};
+use std::{env, fs, process};
+
+const USAGE: &str = "\
+Finds the first point where two runs of a CHIP-8 program diverge.
+
+Usage:
+  chip8-diff traces <left.jsonl> <right.jsonl> [--context N]
+  chip8-diff run <rom> --quirks <left>,<right>
+             [--cycles N] [--seed N] [--inputs FILE] [--context N]
+
+Traces are JSON lines exported by the emulator's tracer.
+Quirks are original or new for each run, e.g. `--quirks original,new`.
+Input files have one `<cycle> <key>` per line, `-` instead of the key releases it.
+Exits with 0 if the runs match, 1 if they diverge and 2 on errors.";
+
+struct Options {
+    positional: Vec<String>,
+    /** Compatibility of the left and right runs */
+    quirks: Option<(Compatibility, Compatibility)>,
+    cycles: u64,
+    seed: u64,
+    inputs: InputScript,
+    context: usize,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("traces") => parse_options(&args[1..]).and_then(|options| diff_traces(&options)),
+        Some("run") => parse_options(&args[1..]).and_then(|options| diff_runs(&options)),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(None) => println!("No divergence found"),
+        Ok(Some(divergence)) => {
+            print!("{}", divergence);
+            process::exit(1);
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    }
+}
+
+fn diff_traces(options: &Options) -> Result<Option<Divergence>, String> {
+    let [left, right] = options.positional.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+    let left = read_trace(left)?;
+    let right = read_trace(right)?;
+
+    Ok(compare_traces(&left, &right, options.context))
+}
+
+fn diff_runs(options: &Options) -> Result<Option<Divergence>, String> {
+    let [rom] = options.positional.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+    let Some((left, right)) = options.quirks else {
+        return Err("--quirks <left>,<right> is required".to_string());
+    };
+    let rom = fs::read(rom).map_err(|e| format!("Failed reading {}: {}", rom, e))?;
+
+    let mut emulators = [left, right].map(|compatibility| {
+        let mut emulator = Emulator::headless(compatibility);
+        emulator.set_seed(options.seed);
+        emulator
+    });
+    for emulator in &mut emulators {
+        emulator.load_rom(rom.clone()).map_err(|e| e.to_string())?;
+    }
+    let [left, right] = &mut emulators;
+
+    Ok(compare_runs(
+        left,
+        right,
+        &options.inputs,
+        options.cycles,
+        options.context,
+    ))
+}
+
+fn read_trace(path: &str) -> Result<Vec<trace::TraceEntry>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed reading {}: {}", path, e))?;
+    trace::from_json_lines(&text).map_err(|e| format!("Failed parsing {}: {}", path, e))
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        positional: Vec::new(),
+        quirks: None,
+        cycles: 1_000_000,
+        seed: 0,
+        inputs: InputScript::default(),
+        context: 10,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.positional.push(arg.clone());
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid number for {}: {}", arg, value))
+        };
+        match arg.as_str() {
+            "--quirks" => {
+                let (left, right) = value
+                    .split_once(',')
+                    .ok_or_else(|| format!("Expected --quirks <left>,<right>: {}", value))?;
+                let parse = |name: &str| name.parse::<Compatibility>().map_err(|e| e.to_string());
+                options.quirks = Some((parse(left)?, parse(right)?));
+            }
+            "--cycles" => options.cycles = number()?,
+            "--seed" => options.seed = number()?,
+            "--context" => options.context = number()? as usize,
+            "--inputs" => {
+                let text = fs::read_to_string(value)
+                    .map_err(|e| format!("Failed reading {}: {}", value, e))?;
+                options.inputs = InputScript::parse(&text).map_err(|e| e.to_string())?;
+            }
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,140 @@
+// Native only like the `gdb` module it serves, `wasm-pack test` builds every binary for wasm32
+#![cfg_attr(target_arch = "wasm32", no_main)]
+#![cfg(not(target_arch = "wasm32"))]
+
+use chip8_emulator::gdb::{self, Connection};
+use chip8_emulator::{Compatibility, Emulator, Platform};
+use std::io::{self, Read, Stdin, Stdout, Write};
+use std::net::TcpListener;
+use std::{env, fs, process};
+
+const USAGE: &str = "\
+Debugs a CHIP-8 program with any GDB remote serial protocol client.
+
+Usage:
+  chip8-gdb <rom> [--port N | --stdio] [--quirks original|new]
+            [--platform chip8|chip8-hires|eti660] [--seed N]
+
+Listens on 127.0.0.1 (port 1234 by default) for a single client, e.g. `target remote :1234`,
+or talks over stdin and stdout with --stdio, e.g. `target remote | chip8-gdb game.ch8 --stdio`.
+Registers are V0-VF, I, PC, DT and ST, the address space is the 4 KiB of memory.";
+
+struct Options {
+    rom: String,
+    port: u16,
+    stdio: bool,
+    compatibility: Compatibility,
+    platform: Platform,
+    seed: Option<u64>,
+}
+
+/** Stdin and stdout as one stream, interrupts can't be polled without blocking */
+struct Stdio {
+    stdin: Stdin,
+    stdout: Stdout,
+}
+impl Read for Stdio {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stdin.read(buf)
+    }
+}
+impl Write for Stdio {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdout.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
+impl Connection for Stdio {}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(message) = parse_options(&args).and_then(|options| debug(&options)) {
+        eprintln!("{}", message);
+        process::exit(2);
+    }
+}
+
+fn debug(options: &Options) -> Result<(), String> {
+    let rom =
+        fs::read(&options.rom).map_err(|e| format!("Failed reading {}: {}", options.rom, e))?;
+    let mut emulator = Emulator::headless(options.compatibility);
+    emulator.set_platform(options.platform);
+    if let Some(seed) = options.seed {
+        emulator.set_seed(seed);
+    }
+    emulator.load_rom(rom).map_err(|e| e.to_string())?;
+
+    let result = if options.stdio {
+        gdb::serve(
+            &mut emulator,
+            Stdio {
+                stdin: io::stdin(),
+                stdout: io::stdout(),
+            },
+        )
+    } else {
+        let listener = TcpListener::bind(("127.0.0.1", options.port))
+            .map_err(|e| format!("Failed listening on port {}: {}", options.port, e))?;
+        eprintln!("Waiting for a GDB client on 127.0.0.1:{}", options.port);
+        let (stream, address) = listener.accept().map_err(|e| e.to_string())?;
+        eprintln!("Client connected from {}", address);
+        stream.set_nodelay(true).map_err(|e| e.to_string())?;
+
+        gdb::serve(&mut emulator, stream)
+    };
+    result.map_err(|e| format!("Connection failed: {}", e))
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        rom: String::new(),
+        port: 1234,
+        stdio: false,
+        compatibility: Compatibility::Original,
+        platform: Platform::Chip8,
+        seed: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.rom = arg.clone();
+            continue;
+        }
+        if arg == "--stdio" {
+            options.stdio = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--port" => {
+                options.port = value
+                    .parse()
+                    .map_err(|_| format!("Invalid port: {}", value))?
+            }
+            "--seed" => {
+                let seed = value
+                    .parse()
+                    .map_err(|_| format!("Invalid number for {}: {}", arg, value))?;
+                options.seed = Some(seed);
+            }
+            "--quirks" => {
+                options.compatibility = value.parse::<Compatibility>().map_err(|e| e.to_string())?
+            }
+            "--platform" => {
+                options.platform = value.parse::<Platform>().map_err(|e| e.to_string())?
+            }
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    if options.rom.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
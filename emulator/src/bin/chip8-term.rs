@@ -0,0 +1,301 @@
+// Native only, crossterm doesn't build for wasm32 and `wasm-pack test` builds every binary for it
+#![cfg_attr(target_arch = "wasm32", no_main)]
+#![cfg(not(target_arch = "wasm32"))]
+
+use chip8_emulator::{snapshot, Compatibility, Emulator, Platform};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use std::{env, fs, process, thread};
+
+const USAGE: &str = "\
+Plays a CHIP-8 program in the terminal.
+
+Usage:
+  chip8-term <rom> [--quirks original|new] [--platform chip8|chip8-hires|eti660] [--seed N]
+             [--keys KEYS] [--braille] [--mute]
+
+--keys takes the keyboard keys for CHIP-8 keys 0 to F in order, 1234/QWER/ASDF/ZXCV by default.
+The display uses half blocks, or braille patterns which take less room with --braille.
+Sound rings the terminal bell unless --mute is given.
+
+Controls: Space pauses, Enter steps one instruction while paused, Backspace resets, Esc quits.";
+
+/** Keys for CHIP-8 keys 0 to F laid out like the COSMAC VIP keypad on 1234/QWER/ASDF/ZXCV */
+const DEFAULT_KEYS: &str = "x123qweasdzc4rfv";
+/** Frames a key stays down after a press when the terminal doesn't report releases, longer than the repeat delay */
+const HOLD_FRAMES: u32 = 30;
+const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+struct Options {
+    rom: String,
+    compatibility: Compatibility,
+    platform: Platform,
+    seed: Option<u64>,
+    keys: Vec<char>,
+    braille: bool,
+    mute: bool,
+}
+
+struct Frontend {
+    emulator: Emulator,
+    options: Options,
+    paused: bool,
+    /** Key held down and the frames it stays down for if releases aren't reported */
+    held_key: Option<(u8, u32)>,
+    /** Whether the terminal reports key releases */
+    releases: bool,
+    beeping: bool,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(message) = parse_options(&args).and_then(play) {
+        eprintln!("{}", message);
+        process::exit(2);
+    }
+}
+
+fn play(options: Options) -> Result<(), String> {
+    let rom =
+        fs::read(&options.rom).map_err(|e| format!("Failed reading {}: {}", options.rom, e))?;
+    let mut emulator = Emulator::headless(options.compatibility);
+    emulator.set_platform(options.platform);
+    if let Some(seed) = options.seed {
+        emulator.set_seed(seed);
+    }
+    emulator.load_rom(rom).map_err(|e| e.to_string())?;
+
+    let mut frontend = Frontend {
+        emulator,
+        options,
+        paused: false,
+        held_key: None,
+        releases: matches!(terminal::supports_keyboard_enhancement(), Ok(true)),
+        beeping: false,
+    };
+
+    let mut stdout = io::stdout();
+    frontend.enter(&mut stdout).map_err(|e| e.to_string())?;
+    let result = frontend.run(&mut stdout);
+    frontend.leave(&mut stdout).map_err(|e| e.to_string())?;
+
+    result.map_err(|e| e.to_string())
+}
+
+impl Frontend {
+    fn enter(&self, stdout: &mut impl Write) -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+        if self.releases {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+        }
+        Ok(())
+    }
+    fn leave(&self, stdout: &mut impl Write) -> io::Result<()> {
+        if self.releases {
+            execute!(stdout, PopKeyboardEnhancementFlags)?;
+        }
+        execute!(stdout, cursor::Show, LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()
+    }
+
+    fn run(&mut self, stdout: &mut impl Write) -> io::Result<()> {
+        loop {
+            let frame_start = Instant::now();
+
+            while event::poll(Duration::ZERO)? {
+                if let Event::Key(key) = event::read()? {
+                    if !self.handle_key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+            self.release_held_key();
+
+            if !self.paused {
+                self.emulator.run(Emulator::CYCLES_PER_FRAME);
+            }
+            self.draw(stdout)?;
+
+            thread::sleep(FRAME.saturating_sub(frame_start.elapsed()));
+        }
+    }
+
+    /** Returns false when the user quits */
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if key.code == KeyCode::Esc || ctrl_c {
+            return false;
+        }
+
+        let chip8_key = match key.code {
+            KeyCode::Char(c) => self
+                .options
+                .keys
+                .iter()
+                .position(|&k| k == c.to_ascii_lowercase())
+                .map(|key| key as u8),
+            _ => None,
+        };
+        if key.kind == KeyEventKind::Release {
+            if chip8_key.is_some() && chip8_key == self.held_key.map(|(key, _)| key) {
+                self.held_key = None;
+                self.emulator.on_key_up();
+            }
+            return true;
+        }
+
+        match (key.code, chip8_key) {
+            (_, Some(chip8_key)) => {
+                self.held_key = Some((chip8_key, HOLD_FRAMES));
+                self.emulator.press_key(chip8_key);
+            }
+            (KeyCode::Char(' '), None) => self.paused = !self.paused,
+            (KeyCode::Enter, None) if self.paused => self.emulator.cycle(),
+            (KeyCode::Backspace, None) => {
+                // Can't fail, the ROM already fit in memory when it was loaded
+                let _ = self.emulator.reset_hard();
+            }
+            _ => {}
+        }
+        true
+    }
+    /** Terminals without release events only repeat presses, so keys go up after a while without one */
+    fn release_held_key(&mut self) {
+        if self.releases || self.paused {
+            return;
+        }
+        if let Some((key, frames)) = self.held_key {
+            self.held_key = match frames {
+                0 => {
+                    self.emulator.on_key_up();
+                    None
+                }
+                _ => Some((key, frames - 1)),
+            };
+        }
+    }
+
+    fn draw(&mut self, stdout: &mut impl Write) -> io::Result<()> {
+        let state = self.emulator.state();
+        let lines = match self.options.braille {
+            true => snapshot::to_braille(self.emulator.framebuffer()),
+            false => snapshot::to_half_blocks(self.emulator.framebuffer()),
+        };
+
+        queue!(stdout, cursor::MoveTo(0, 0))?;
+        for line in lines {
+            queue!(stdout, Print(line), cursor::MoveToNextLine(1))?;
+        }
+
+        let status = match (&state.fault, self.paused) {
+            (Some(fault), _) => format!("HALTED: {}", fault),
+            (None, true) => "PAUSED".to_string(),
+            (None, false) => "RUNNING".to_string(),
+        };
+        queue!(
+            stdout,
+            Clear(ClearType::FromCursorDown),
+            Print(status),
+            cursor::MoveToNextLine(1),
+            Print("Space pause  Enter step  Backspace reset  Esc quit"),
+            cursor::MoveToNextLine(2),
+        )?;
+        if self.paused {
+            for line in state.to_string().lines() {
+                queue!(stdout, Print(line), cursor::MoveToNextLine(1))?;
+            }
+        }
+
+        let beeping = state.sound_timer > 0;
+        if beeping && !self.beeping && !self.options.mute {
+            queue!(stdout, Print('\x07'))?;
+        }
+        self.beeping = beeping;
+
+        stdout.flush()
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        rom: String::new(),
+        compatibility: Compatibility::Original,
+        platform: Platform::Chip8,
+        seed: None,
+        keys: DEFAULT_KEYS.chars().collect(),
+        braille: false,
+        mute: false,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.rom = arg.clone();
+            continue;
+        }
+        match arg.as_str() {
+            "--braille" => {
+                options.braille = true;
+                continue;
+            }
+            "--mute" => {
+                options.mute = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--seed" => {
+                let seed = value
+                    .parse()
+                    .map_err(|_| format!("Invalid number for {}: {}", arg, value))?;
+                options.seed = Some(seed);
+            }
+            "--quirks" => {
+                options.compatibility = value.parse::<Compatibility>().map_err(|e| e.to_string())?
+            }
+            "--platform" => {
+                options.platform = value.parse::<Platform>().map_err(|e| e.to_string())?
+            }
+            "--keys" => options.keys = parse_keys(value)?,
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    if options.rom.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
+
+fn parse_keys(value: &str) -> Result<Vec<char>, String> {
+    let keys: Vec<char> = value.to_lowercase().chars().collect();
+    let unique = keys
+        .iter()
+        .enumerate()
+        .all(|(i, key)| !keys[..i].contains(key));
+    if keys.len() != 16 || !unique || keys.contains(&' ') {
+        return Err(format!(
+            "Invalid keys {}, expected 16 different keys for CHIP-8 keys 0 to F",
+            value
+        ));
+    }
+
+    Ok(keys)
+}
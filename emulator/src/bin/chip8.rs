@@ -0,0 +1,156 @@
+use chip8_emulator::input::InputScript;
+use chip8_emulator::{snapshot, Compatibility, Emulator, Platform};
+use std::{env, fs, process};
+
+const USAGE: &str = "\
+Runs a CHIP-8 program without a browser and prints the display it ends with.
+
+Usage:
+  chip8 <rom> [--frames N] [--quirks original|new] [--platform chip8|chip8-hires|eti660]
+        [--seed N] [--inputs FILE] [--png FILE] [--scale N] [--state]
+
+Runs for 600 frames (10 seconds) by default, stopping early when the program halts
+on a fatal fault or a jump to itself. A frame is 10 cycles, one tick of the timers.
+Input files have one `<cycle> <key>` per line, `-` instead of the key releases it.
+Prints the display as text unless --png is given, --state also prints the registers.
+PNG pixels are --scale pixels wide, from 1 to 64 and 8 by default.
+Exits with 0 when done, 1 if the program hit a fatal fault and 2 on errors.";
+
+/** Largest --scale, a 4096x2048 PNG */
+const MAX_SCALE: u64 = 64;
+
+struct Options {
+    rom: String,
+    frames: u64,
+    compatibility: Compatibility,
+    platform: Platform,
+    seed: Option<u64>,
+    inputs: InputScript,
+    png: Option<String>,
+    scale: usize,
+    state: bool,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = parse_options(&args).and_then(|options| run(&options));
+
+    match result {
+        Ok(None) => {}
+        Ok(Some(fault)) => {
+            eprintln!("{}", fault);
+            process::exit(1);
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    }
+}
+
+/** Returns the fault the program stopped on, if any */
+fn run(options: &Options) -> Result<Option<String>, String> {
+    let rom =
+        fs::read(&options.rom).map_err(|e| format!("Failed reading {}: {}", options.rom, e))?;
+    let mut emulator = Emulator::headless(options.compatibility);
+    emulator.set_platform(options.platform);
+    if let Some(seed) = options.seed {
+        emulator.set_seed(seed);
+    }
+    emulator.load_rom(rom).map_err(|e| e.to_string())?;
+
+    for _ in 0..options.frames {
+        if options.inputs.events().is_empty() {
+            emulator.run(Emulator::CYCLES_PER_FRAME);
+        } else {
+            for _ in 0..Emulator::CYCLES_PER_FRAME {
+                emulator.apply_inputs(&options.inputs);
+                emulator.run(1);
+            }
+        }
+        if emulator.is_halted() {
+            break;
+        }
+    }
+
+    match &options.png {
+        Some(path) => {
+            let png = snapshot::to_png(emulator.framebuffer(), options.scale);
+            fs::write(path, png).map_err(|e| format!("Failed writing {}: {}", path, e))?;
+        }
+        None => print!("{}", snapshot::to_text(emulator.framebuffer())),
+    }
+    if options.state {
+        print!("{}", emulator.state());
+    }
+
+    Ok(emulator.fault())
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        rom: String::new(),
+        frames: 600,
+        compatibility: Compatibility::Original,
+        platform: Platform::Chip8,
+        seed: None,
+        inputs: InputScript::default(),
+        png: None,
+        scale: 8,
+        state: false,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.rom = arg.clone();
+            continue;
+        }
+        if arg == "--state" {
+            options.state = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid number for {}: {}", arg, value))
+        };
+        match arg.as_str() {
+            "--frames" => options.frames = number()?,
+            "--seed" => options.seed = Some(number()?),
+            "--scale" => {
+                options.scale = number()
+                    .ok()
+                    .filter(|scale| (1..=MAX_SCALE).contains(scale))
+                    .ok_or_else(|| {
+                        format!(
+                            "--scale has to be from 1 to {}: {}\n\n{}",
+                            MAX_SCALE, value, USAGE
+                        )
+                    })? as usize
+            }
+            "--quirks" => {
+                options.compatibility = value.parse::<Compatibility>().map_err(|e| e.to_string())?
+            }
+            "--platform" => {
+                options.platform = value.parse::<Platform>().map_err(|e| e.to_string())?
+            }
+            "--png" => options.png = Some(value.clone()),
+            "--inputs" => {
+                let text = fs::read_to_string(value)
+                    .map_err(|e| format!("Failed reading {}: {}", value, e))?;
+                options.inputs = InputScript::parse(&text).map_err(|e| e.to_string())?;
+            }
+            _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    if options.rom.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
@@ -0,0 +1,325 @@
+use crate::components::memory::Memory;
+use crate::components::processor::Processor;
+use crate::expression::{Expression, ExpressionError};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+/** Parsed condition with the text it came from */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub text: String,
+    expression: Expression,
+}
+impl Condition {
+    pub fn parse(text: &str) -> Result<Condition, ExpressionError> {
+        Ok(Condition {
+            text: text.trim().to_string(),
+            expression: Expression::parse(text)?,
+        })
+    }
+    pub fn is_met(&self, processor: &Processor, hit_count: u64) -> bool {
+        self.expression.evaluate(processor, hit_count) != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MessagePart {
+    Text(String),
+    Value { expression: Expression, hex: bool },
+}
+
+/**
+ * Logpoint message with expressions in braces, `{expression}` prints in decimal and `{expression:x}` in hex,
+ * e.g. `V3 is {v3}, I is {i:x}`. Literal braces are written as `{{` and `}}`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogMessage {
+    pub text: String,
+    parts: Vec<MessagePart>,
+}
+impl LogMessage {
+    pub fn parse(text: &str) -> Result<LogMessage, ExpressionError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(index) = rest.find(['{', '}']) {
+            let position = text.len() - rest.len() + index;
+            literal += &rest[..index];
+            let brace = &rest[index..index + 1];
+            rest = &rest[index + 1..];
+
+            if let Some(escaped) = rest.strip_prefix(brace) {
+                literal += brace;
+                rest = escaped;
+                continue;
+            }
+            if brace == "}" {
+                return Err(ExpressionError {
+                    position,
+                    message: "Unmatched `}`".to_string(),
+                });
+            }
+
+            let end = rest.find('}').ok_or_else(|| ExpressionError {
+                position,
+                message: "Unmatched `{`".to_string(),
+            })?;
+            let (source, hex) = match rest[..end].strip_suffix(":x") {
+                Some(source) => (source, true),
+                None => (&rest[..end], false),
+            };
+            let expression = Expression::parse(source).map_err(|err| ExpressionError {
+                position: position + 1 + err.position,
+                ..err
+            })?;
+
+            if !literal.is_empty() {
+                parts.push(MessagePart::Text(std::mem::take(&mut literal)));
+            }
+            parts.push(MessagePart::Value { expression, hex });
+            rest = &rest[end + 1..];
+        }
+        literal += rest;
+        if !literal.is_empty() {
+            parts.push(MessagePart::Text(literal));
+        }
+
+        Ok(LogMessage {
+            text: text.to_string(),
+            parts,
+        })
+    }
+    pub fn format(&self, processor: &Processor, hit_count: u64) -> String {
+        let mut message = String::new();
+        for part in &self.parts {
+            match part {
+                MessagePart::Text(text) => message += text,
+                MessagePart::Value { expression, hex } => {
+                    let value = expression.evaluate(processor, hit_count);
+                    message += &match hex {
+                        true => format!("{:#X}", value),
+                        false => value.to_string(),
+                    };
+                }
+            }
+        }
+
+        message
+    }
+}
+
+/** Stops execution when reached and its condition holds, or logs a message and keeps going if it's a logpoint */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub condition: Option<Condition>,
+    pub log_message: Option<LogMessage>,
+    /** Times the address was reached, whether the condition held or not */
+    pub hit_count: u64,
+}
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(condition) = &self.condition {
+            parts.push(format!("if {}", condition.text));
+        }
+        if let Some(log_message) = &self.log_message {
+            parts.push(format!("log \"{}\"", log_message.text));
+        }
+        parts.push(format!("hits {}", self.hit_count));
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/** Breakpoints by address, with a lookup table so addresses without one cost a single check per cycle */
+#[derive(Debug)]
+pub struct Breakpoints {
+    breakpoints: BTreeMap<u16, Breakpoint>,
+    armed: Vec<bool>,
+    /** Output of logpoints, oldest first */
+    log: VecDeque<String>,
+}
+impl Breakpoints {
+    /** Lines of logpoint output kept before the oldest ones get dropped */
+    pub const LOG_CAPACITY: usize = 1024;
+
+    pub fn init() -> Breakpoints {
+        Breakpoints {
+            breakpoints: BTreeMap::new(),
+            armed: vec![false; Memory::SIZE],
+            log: VecDeque::new(),
+        }
+    }
+
+    /** Replaces any breakpoint at the same address */
+    pub fn insert(&mut self, address: u16, breakpoint: Breakpoint) {
+        if let Some(armed) = self.armed.get_mut(address as usize) {
+            *armed = true;
+            self.breakpoints.insert(address, breakpoint);
+        }
+    }
+    pub fn remove(&mut self, address: u16) {
+        if self.breakpoints.remove(&address).is_some() {
+            self.armed[address as usize] = false;
+        }
+    }
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.armed.fill(false);
+    }
+
+    pub fn is_armed(&self, address: u16) -> bool {
+        self.armed.get(address as usize).copied().unwrap_or(false)
+    }
+    pub fn get(&self, address: u16) -> Option<&Breakpoint> {
+        self.breakpoints.get(&address)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&u16, &Breakpoint)> {
+        self.breakpoints.iter()
+    }
+    /** Counts reaching the breakpoint at `address`, returns the new hit count */
+    pub fn count_hit(&mut self, address: u16) -> Option<u64> {
+        let breakpoint = self.breakpoints.get_mut(&address)?;
+        breakpoint.hit_count += 1;
+
+        Some(breakpoint.hit_count)
+    }
+
+    pub fn log(&mut self, message: String) {
+        if self.log.len() == Breakpoints::LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(message);
+    }
+    /** Returns logpoint output written since the last call */
+    pub fn take_log(&mut self) -> Vec<String> {
+        self.log.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Breakpoint, Breakpoints, Condition, LogMessage};
+    use crate::components::processor::Processor;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /** 0x200: V0 += 1, 0x202: jump to 0x200 */
+    fn counting_processor() -> Processor {
+        let mut processor = Processor::init();
+        processor
+            .memory
+            .write_bytes(0x200, &[0x70, 0x01, 0x12, 0x00])
+            .unwrap();
+
+        processor
+    }
+
+    #[wasm_bindgen_test]
+    fn test_log_message() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.v[0x3] = 0x10;
+        processor.i = 0x2A4;
+
+        // Act
+        let message =
+            LogMessage::parse("{{V3}} is {v3}, I is {i:x} after {hit_count} hits").unwrap();
+
+        // Assert
+        assert_eq!(
+            message.format(&processor, 2),
+            "{V3} is 16, I is 0x2A4 after 2 hits"
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_log_message_error() {
+        // Arrange
+        let texts = ["{v3", "v3}", "V3 is {v3 +}"];
+
+        // Act
+        let results = texts.map(LogMessage::parse);
+
+        // Assert
+        let positions = results.map(|result| result.unwrap_err().position);
+        assert_eq!(positions, [0, 2, 11]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_conditional_breakpoint() {
+        // Arrange
+        let mut processor = counting_processor();
+        let breakpoint = Breakpoint {
+            condition: Some(Condition::parse("v0 == 3").unwrap()),
+            ..Breakpoint::default()
+        };
+        processor.breakpoints.insert(0x202, breakpoint);
+
+        // Act
+        let ran = processor.run(100);
+
+        // Assert
+        assert_eq!(ran, 5);
+        assert_eq!(processor.hit_breakpoint, Some(0x202));
+        assert_eq!(processor.v[0x0], 3);
+        assert_eq!(processor.breakpoints.get(0x202).unwrap().hit_count, 3);
+    }
+    #[wasm_bindgen_test]
+    fn test_breakpoint_hit_count() {
+        // Arrange
+        let mut processor = counting_processor();
+        let breakpoint = Breakpoint {
+            condition: Some(Condition::parse("hit_count > 5").unwrap()),
+            ..Breakpoint::default()
+        };
+        processor.breakpoints.insert(0x200, breakpoint);
+
+        // Act
+        let first = processor.run(100);
+        let second = processor.run(100);
+
+        // Assert
+        assert_eq!(first, 10);
+        assert_eq!(
+            second, 2,
+            "Run should continue past the breakpoint it stopped at!"
+        );
+        assert_eq!(processor.v[0x0], 6);
+    }
+    #[wasm_bindgen_test]
+    fn test_logpoint() {
+        // Arrange
+        let mut processor = counting_processor();
+        let logpoint = Breakpoint {
+            condition: Some(Condition::parse("v0 & 1").unwrap()),
+            log_message: Some(LogMessage::parse("V0 is {v0}").unwrap()),
+            ..Breakpoint::default()
+        };
+        processor.breakpoints.insert(0x202, logpoint);
+
+        // Act
+        let ran = processor.run(8);
+
+        // Assert
+        assert_eq!(ran, 8, "Logpoints should not stop execution!");
+        assert_eq!(processor.hit_breakpoint, None);
+        assert_eq!(processor.breakpoints.take_log(), ["V0 is 1", "V0 is 3"]);
+        assert!(processor.breakpoints.take_log().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_remove() {
+        // Arrange
+        let mut breakpoints = Breakpoints::init();
+        breakpoints.insert(0x200, Breakpoint::default());
+        breakpoints.insert(0x202, Breakpoint::default());
+
+        // Act
+        breakpoints.remove(0x200);
+
+        // Assert
+        assert!(!breakpoints.is_armed(0x200));
+        assert!(breakpoints.is_armed(0x202));
+        assert_eq!(breakpoints.iter().count(), 1);
+    }
+}
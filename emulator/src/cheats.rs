@@ -0,0 +1,391 @@
+use crate::components::memory::Memory;
+use std::collections::HashMap;
+use std::fmt;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+/** How candidates are compared against the previous snapshot when narrowing a RAM search */
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /** Equal to the given value */
+    Equal,
+    /** Not equal to the given value */
+    NotEqual,
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+/** Narrows down addresses holding a value, e.g. lives, by comparing memory between snapshots */
+#[derive(Debug, Default)]
+pub struct RamSearch {
+    snapshot: Vec<u8>,
+    candidates: Vec<u16>,
+}
+impl RamSearch {
+    /** Takes a snapshot and makes every address a candidate */
+    pub fn start(&mut self, memory: &Memory) {
+        self.snapshot = memory.data().to_vec();
+        self.candidates = (0..Memory::SIZE as u16).collect();
+    }
+    /** Keeps the candidates which pass the comparison and takes a new snapshot, returns how many are left */
+    pub fn narrow(&mut self, memory: &Memory, comparison: Comparison, value: u8) -> usize {
+        if self.snapshot.is_empty() {
+            self.start(memory);
+        }
+
+        let snapshot = &self.snapshot;
+        self.candidates.retain(|&address| {
+            let old = snapshot[address as usize];
+            let new = memory.data()[address as usize];
+            match comparison {
+                Comparison::Equal => new == value,
+                Comparison::NotEqual => new != value,
+                Comparison::Changed => new != old,
+                Comparison::Unchanged => new == old,
+                Comparison::Increased => new > old,
+                Comparison::Decreased => new < old,
+            }
+        });
+        self.snapshot = memory.data().to_vec();
+
+        self.candidates.len()
+    }
+    pub fn candidates(&self) -> &[u16] {
+        &self.candidates
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cheat {
+    /** Rewrites the value after every cycle, so the program can't change it */
+    Freeze { address: u16, value: u8 },
+    /** Writes the bytes once, when added and whenever the ROM is (re)loaded */
+    Patch { address: u16, bytes: Vec<u8> },
+}
+impl Cheat {
+    /** Number of bytes written */
+    pub fn size(&self) -> usize {
+        match self {
+            Cheat::Freeze { .. } => 1,
+            Cheat::Patch { bytes, .. } => bytes.len(),
+        }
+    }
+    /** Whether every written byte is inside memory */
+    pub fn fits(&self) -> bool {
+        let (Cheat::Freeze { address, .. } | Cheat::Patch { address, .. }) = self;
+        *address as usize + self.size() <= Memory::SIZE
+    }
+    pub fn apply(&self, memory: &mut Memory) {
+        let (address, bytes) = match self {
+            Cheat::Freeze { address, value } => (*address, std::slice::from_ref(value)),
+            Cheat::Patch { address, bytes } => (*address, bytes.as_slice()),
+        };
+        // Cheats which don't fit are rejected when created
+        memory
+            .write_bytes(address, bytes)
+            .expect("Cheat doesn't fit in memory!");
+    }
+}
+impl fmt::Display for Cheat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cheat::Freeze { address, value } => write!(f, "freeze {:#05X} {:02X}", address, value),
+            Cheat::Patch { address, bytes } => {
+                write!(f, "patch {:#05X} ", address)?;
+                for byte in bytes {
+                    write!(f, "{:02X}", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CheatError {
+    pub line: usize,
+    pub message: String,
+}
+impl fmt::Display for CheatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+impl std::error::Error for CheatError {}
+impl From<CheatError> for JsValue {
+    fn from(err: CheatError) -> JsValue {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/**
+ * Cheats for any number of ROMs, grouped under the SHA-1 hash of the ROM they're for:
+ *
+ * ```text
+ * # Brix
+ * [f13766c14aeb02ad8d4d103cb5eadd282d20cddc]
+ * freeze 0x2F5 03
+ * patch 0x300 00E01200
+ * ```
+ */
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CheatFile {
+    cheats: HashMap<String, Vec<Cheat>>,
+}
+impl CheatFile {
+    pub fn parse(text: &str) -> Result<CheatFile, CheatError> {
+        let mut file = CheatFile::default();
+        let mut hash: Option<String> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| CheatError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                hash = Some(section.trim().to_lowercase());
+                continue;
+            }
+            let Some(hash) = &hash else {
+                return Err(error("Expected a [ROM hash] before the first cheat"));
+            };
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let [kind, address, value] = parts[..] else {
+                return Err(error(
+                    "Expected `freeze <address> <value>` or `patch <address> <bytes>`",
+                ));
+            };
+            let address = address
+                .strip_prefix("0x")
+                .or_else(|| address.strip_prefix("0X"))
+                .unwrap_or(address);
+            let address = u16::from_str_radix(address, 16).map_err(|_| error("Invalid address"))?;
+            let bytes = parse_hex(value).ok_or_else(|| error("Invalid hex value"))?;
+
+            let cheat = match (kind, bytes.as_slice()) {
+                ("freeze", &[value]) => Cheat::Freeze { address, value },
+                ("freeze", _) => return Err(error("Frozen values have to be a single byte")),
+                ("patch", _) => Cheat::Patch { address, bytes },
+                _ => return Err(error("Unknown cheat, expected freeze or patch")),
+            };
+            if !cheat.fits() {
+                return Err(error("Cheat doesn't fit in memory"));
+            }
+
+            file.cheats.entry(hash.clone()).or_default().push(cheat);
+        }
+
+        Ok(file)
+    }
+
+    /** Cheats for the given ROM, empty if there are none */
+    pub fn cheats_for(&self, rom: &[u8]) -> &[Cheat] {
+        self.cheats
+            .get(&rom_hash(rom))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+    /** Replaces the cheats for the given ROM, dropping its section if there are none left */
+    pub fn insert(&mut self, rom: &[u8], cheats: Vec<Cheat>) {
+        if cheats.is_empty() {
+            self.cheats.remove(&rom_hash(rom));
+        } else {
+            self.cheats.insert(rom_hash(rom), cheats);
+        }
+    }
+}
+impl fmt::Display for CheatFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hashes: Vec<&String> = self.cheats.keys().collect();
+        hashes.sort();
+
+        for hash in hashes {
+            writeln!(f, "[{}]", hash)?;
+            for cheat in &self.cheats[hash] {
+                writeln!(f, "{}", cheat)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/** Lowercase hex SHA-1 of the ROM, the key of its cheats */
+pub fn rom_hash(rom: &[u8]) -> String {
+    sha1_smol::Sha1::from(rom).digest().to_string()
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if text.is_empty() || !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rom_hash, Cheat, CheatFile, Comparison, RamSearch};
+    use crate::components::memory::Memory;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_ram_search() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.write(0x300, 3);
+        memory.write(0x400, 3);
+        let mut search = RamSearch::default();
+        search.start(&memory);
+
+        // Act
+        let equal = search.narrow(&memory, Comparison::Equal, 3);
+        memory.write(0x300, 2);
+        let decreased = search.narrow(&memory, Comparison::Decreased, 0);
+
+        // Assert
+        assert_eq!(equal, 2);
+        assert_eq!(decreased, 1);
+        assert_eq!(search.candidates(), [0x300]);
+    }
+    #[wasm_bindgen_test]
+    fn test_ram_search_unchanged() {
+        // Arrange
+        let mut memory = Memory::init();
+        let mut search = RamSearch::default();
+        search.start(&memory);
+        memory.write(0x300, 1);
+
+        // Act
+        let result = search.narrow(&memory, Comparison::Unchanged, 0);
+
+        // Assert
+        assert_eq!(result, Memory::SIZE - 1);
+        assert!(!search.candidates().contains(&0x300));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cheat_apply() {
+        // Arrange
+        let mut memory = Memory::init();
+        let cheats = [
+            Cheat::Freeze {
+                address: 0x300,
+                value: 0x09,
+            },
+            Cheat::Patch {
+                address: 0x400,
+                bytes: vec![0x00, 0xE0],
+            },
+        ];
+
+        // Act
+        for cheat in &cheats {
+            cheat.apply(&mut memory);
+        }
+
+        // Assert
+        assert_eq!(memory.data()[0x300], 0x09);
+        assert_eq!(memory.data()[0x400..0x402], [0x00, 0xE0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cheat_file_parse() {
+        // Arrange
+        let rom = [0x12, 0x00];
+        let text = format!(
+            "# Loop\n[{}]\nfreeze 0X2F5 03\npatch 0x300 00E01200\n[0000]\nfreeze 0x200 00\n",
+            rom_hash(&rom).to_uppercase()
+        );
+
+        // Act
+        let result = CheatFile::parse(&text);
+
+        // Assert
+        let file = result.unwrap();
+        assert_eq!(
+            file.cheats_for(&rom),
+            [
+                Cheat::Freeze {
+                    address: 0x2F5,
+                    value: 0x03
+                },
+                Cheat::Patch {
+                    address: 0x300,
+                    bytes: vec![0x00, 0xE0, 0x12, 0x00]
+                }
+            ]
+        );
+        assert!(file.cheats_for(&[0x00]).is_empty());
+    }
+    #[wasm_bindgen_test]
+    fn test_cheat_file_parse_errors() {
+        // Arrange
+        let texts = [
+            "freeze 0x200 00",
+            "[0000]\nfreeze 0x200 0011",
+            "[0000]\nfreeze 0xFFF",
+            "[0000]\npatch 0xFFF 0011",
+            "[0000]\nfreeze 0x200 0G",
+        ];
+
+        // Act
+        let results = texts.map(CheatFile::parse);
+
+        // Assert
+        for result in results {
+            assert!(result.is_err());
+        }
+    }
+    #[wasm_bindgen_test]
+    fn test_cheat_file_round_trip() {
+        // Arrange
+        let mut file = CheatFile::default();
+        file.insert(
+            &[0x12, 0x00],
+            vec![Cheat::Freeze {
+                address: 0x2F5,
+                value: 0x03,
+            }],
+        );
+
+        // Act
+        let result = CheatFile::parse(&file.to_string());
+
+        // Assert
+        assert_eq!(result.unwrap(), file);
+    }
+    #[wasm_bindgen_test]
+    fn test_cheat_file_insert_empty() {
+        // Arrange
+        let mut file = CheatFile::default();
+        let cheats = vec![Cheat::Freeze {
+            address: 0x2F5,
+            value: 0x03,
+        }];
+        file.insert(&[0x12, 0x00], cheats.clone());
+        file.insert(&[0x00, 0xE0], cheats);
+
+        // Act
+        file.insert(&[0x12, 0x00], Vec::new());
+
+        // Assert
+        assert_eq!(
+            file.to_string(),
+            format!("[{}]\nfreeze 0x2F5 03\n", rom_hash(&[0x00, 0xE0])),
+            "Sections without cheats shouldn't be written!"
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use crate::components::processor::Processor;
+use crate::input::InputScript;
+use crate::instruction::decode;
+use crate::trace::{RegisterState, TraceEntry};
+use crate::Emulator;
+use std::collections::VecDeque;
+use std::fmt;
+
+/** First point where two runs stopped matching */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /** Cycle after which the difference showed up, 0 means before the first instruction */
+    pub cycle: u64,
+    /** Address of the instruction which caused the difference */
+    pub pc: u16,
+    /** What differs, e.g. `V3`, `memory 0x0300` or `pixel (12, 4)` */
+    pub location: String,
+    pub left: String,
+    pub right: String,
+    /** Instructions executed right before the divergence, the last one caused it */
+    pub context: Vec<String>,
+}
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Runs diverge after cycle {} at PC {:#06X}: {} is {} on the left and {} on the right",
+            self.cycle, self.pc, self.location, self.left, self.right
+        )?;
+        for line in &self.context {
+            writeln!(f, "  {}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/** Compares two recorded traces entry by entry, `context` is the number of preceding entries kept */
+pub fn compare_traces(
+    left: &[TraceEntry],
+    right: &[TraceEntry],
+    context: usize,
+) -> Option<Divergence> {
+    let mut lines = VecDeque::with_capacity(context + 1);
+
+    for (left_entry, right_entry) in left.iter().zip(right) {
+        push_context(&mut lines, context, left_entry.to_string());
+
+        let difference = entry_difference(left_entry, right_entry);
+        if let Some((location, left, right)) = difference {
+            return Some(Divergence {
+                cycle: left_entry.cycle,
+                pc: left_entry.pc,
+                location,
+                left,
+                right,
+                context: lines.into(),
+            });
+        }
+    }
+
+    if left.len() != right.len() {
+        let last = left.get(right.len()).or_else(|| right.get(left.len()))?;
+        return Some(Divergence {
+            cycle: last.cycle,
+            pc: last.pc,
+            location: "trace length".to_string(),
+            left: left.len().to_string(),
+            right: right.len().to_string(),
+            context: lines.into(),
+        });
+    }
+
+    None
+}
+
+/**
+ * Runs two emulators in lockstep, feeding both the same inputs, and stops at the first cycle
+ * after which their PC, registers, I, timers, stack, memory or display differ.
+ * Both emulators should already have the same ROM and seed loaded.
+ */
+pub fn compare_runs(
+    left: &mut Emulator,
+    right: &mut Emulator,
+    inputs: &InputScript,
+    cycles: u64,
+    context: usize,
+) -> Option<Divergence> {
+    let mut lines = VecDeque::with_capacity(context + 1);
+    let mut pc = left.processor.pc;
+
+    for cycle in 0..=cycles {
+        if let Some((location, left, right)) = state_difference(&left.processor, &right.processor) {
+            return Some(Divergence {
+                cycle,
+                pc,
+                location,
+                left,
+                right,
+                context: lines.into(),
+            });
+        }
+        let halted = left.processor.fault.is_some() && right.processor.fault.is_some();
+        if cycle == cycles || halted {
+            break;
+        }
+
+        inputs.apply(cycle, &mut left.processor.keypad);
+        inputs.apply(cycle, &mut right.processor.keypad);
+
+        pc = left.processor.pc;
+        let opcode = left.processor.fetch();
+        push_context(
+            &mut lines,
+            context,
+            format!(
+                "{:08} {:#06X}: {:04X} {}",
+                cycle + 1,
+                pc,
+                opcode,
+                decode(opcode).mnemonic(left.processor.compatibility)
+            ),
+        );
+
+        left.processor.cycle();
+        right.processor.cycle();
+    }
+
+    None
+}
+
+fn push_context(lines: &mut VecDeque<String>, context: usize, line: String) {
+    lines.push_back(line);
+    while lines.len() > context + 1 {
+        lines.pop_front();
+    }
+}
+
+type Difference = (String, String, String);
+
+fn entry_difference(left: &TraceEntry, right: &TraceEntry) -> Option<Difference> {
+    if left.pc != right.pc {
+        return Some(hex_difference("PC", left.pc, right.pc));
+    }
+    if left.opcode != right.opcode {
+        return Some(hex_difference("opcode", left.opcode, right.opcode));
+    }
+
+    register_difference(&left.before, &right.before)
+        .or_else(|| register_difference(&left.after, &right.after))
+}
+
+fn register_difference(left: &RegisterState, right: &RegisterState) -> Option<Difference> {
+    if left.pc != right.pc {
+        return Some(hex_difference("PC", left.pc, right.pc));
+    }
+    if left.i != right.i {
+        return Some(hex_difference("I", left.i, right.i));
+    }
+    if let Some(x) = (0..16).find(|&x| left.v[x] != right.v[x]) {
+        return Some(hex_difference(&format!("V{:X}", x), left.v[x], right.v[x]));
+    }
+    if left.stack != right.stack {
+        return Some((
+            "stack".to_string(),
+            format!("{:04X?}", left.stack),
+            format!("{:04X?}", right.stack),
+        ));
+    }
+
+    None
+}
+
+fn state_difference(left: &Processor, right: &Processor) -> Option<Difference> {
+    let registers = register_difference(
+        &RegisterState::capture(left),
+        &RegisterState::capture(right),
+    );
+    if registers.is_some() {
+        return registers;
+    }
+    if left.delay_timer != right.delay_timer {
+        return Some(hex_difference("DT", left.delay_timer, right.delay_timer));
+    }
+    if left.sound_timer != right.sound_timer {
+        return Some(hex_difference("ST", left.sound_timer, right.sound_timer));
+    }
+    if left.fault != right.fault {
+        return Some((
+            "fault".to_string(),
+            format!("{:?}", left.fault),
+            format!("{:?}", right.fault),
+        ));
+    }
+
+    // Comparing whole arrays first is much faster than searching for the first difference
+    if left.memory.data() != right.memory.data() {
+        let memory = left.memory.data().iter().zip(right.memory.data().iter());
+        if let Some((address, (l, r))) = memory.enumerate().find(|(_, (l, r))| l != r) {
+            return Some(hex_difference(&format!("memory {:#06X}", address), *l, *r));
+        }
+    }
+
+    if left.gfx != right.gfx {
+        let rows = left.gfx.rows().iter().zip(right.gfx.rows().iter());
+        if let Some((y, (l, r))) = rows.enumerate().find(|(_, (l, r))| l != r) {
+            let x = (l ^ r).leading_zeros() as usize;
+            return Some((
+                format!("pixel ({}, {})", x, y),
+                u8::from(left.gfx.get(x, y)).to_string(),
+                u8::from(right.gfx.get(x, y)).to_string(),
+            ));
+        }
+    }
+
+    None
+}
+
+fn hex_difference<T: fmt::UpperHex>(location: &str, left: T, right: T) -> Difference {
+    (
+        location.to_string(),
+        format!("{:#04X}", left),
+        format!("{:#04X}", right),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_runs, compare_traces};
+    use crate::components::processor::Compatibility;
+    use crate::input::InputScript;
+    use crate::trace::Tracer;
+    use crate::Emulator;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn emulator_with_rom(compatibility: Compatibility, rom: &[u8]) -> Emulator {
+        let mut emulator = Emulator::headless(compatibility);
+        emulator.set_seed(0);
+        emulator.load_rom(rom.to_vec()).unwrap();
+
+        emulator
+    }
+
+    #[wasm_bindgen_test]
+    fn test_compare_runs_identical() {
+        // Arrange
+        // 0x200: V1 += 1, 0x202: jump to 0x200
+        let rom = [0x71, 0x01, 0x12, 0x00];
+        let mut left = emulator_with_rom(Compatibility::Original, &rom);
+        let mut right = emulator_with_rom(Compatibility::New, &rom);
+
+        // Act
+        let result = compare_runs(&mut left, &mut right, &InputScript::default(), 100, 5);
+
+        // Assert
+        assert_eq!(result, None);
+    }
+    #[wasm_bindgen_test]
+    fn test_compare_runs_quirk() {
+        // Arrange
+        // 0x200: V0 = 0x04, 0x202: V1 = 0x08, 0x204: jump to 0x300 + V0 (or V3 on newer systems)
+        let rom = [0x60, 0x04, 0x61, 0x08, 0xB3, 0x00];
+        let mut left = emulator_with_rom(Compatibility::Original, &rom);
+        let mut right = emulator_with_rom(Compatibility::New, &rom);
+
+        // Act
+        let result = compare_runs(&mut left, &mut right, &InputScript::default(), 100, 1);
+
+        // Assert
+        let divergence = result.unwrap();
+        assert_eq!(divergence.cycle, 3);
+        assert_eq!(divergence.pc, 0x204);
+        assert_eq!(divergence.location, "PC");
+        assert_eq!(divergence.left, "0x304");
+        assert_eq!(divergence.right, "0x300");
+        assert_eq!(
+            divergence.context,
+            [
+                "00000002 0x0202: 6108 LD V1, 0x08",
+                "00000003 0x0204: B300 JP V0, 0x300"
+            ]
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_compare_runs_inputs() {
+        // Arrange
+        // 0x200: skip if key in V0 is pressed, 0x202: V1 = 0x01, 0x204: jump to 0x204
+        let rom = [0xE0, 0x9E, 0x61, 0x01, 0x12, 0x04];
+        let mut left = emulator_with_rom(Compatibility::Original, &rom);
+        let mut right = emulator_with_rom(Compatibility::Original, &rom);
+        right.on_key_down("KeyX");
+
+        // Act
+        let result = compare_runs(&mut left, &mut right, &InputScript::default(), 10, 0);
+        let scripted = compare_runs(
+            &mut emulator_with_rom(Compatibility::Original, &rom),
+            &mut emulator_with_rom(Compatibility::Original, &rom),
+            &InputScript::parse("0 0").unwrap(),
+            10,
+            0,
+        );
+
+        // Assert
+        assert_eq!(result.unwrap().location, "PC");
+        assert_eq!(scripted, None, "Both runs should get the same inputs!");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_compare_traces() {
+        // Arrange
+        // 0x200: V0 = 0x04, 0x202: V1 = 0x08, 0x204: jump to 0x300 + V0 (or V3 on newer systems)
+        let rom = [0x60, 0x04, 0x61, 0x08, 0xB3, 0x00];
+        let traces = [Compatibility::Original, Compatibility::New].map(|compatibility| {
+            let mut emulator = emulator_with_rom(compatibility, &rom);
+            let mut tracer = Tracer::init();
+            tracer.start(Tracer::DEFAULT_CAPACITY);
+            for _ in 0..3 {
+                tracer.cycle(&mut emulator.processor);
+            }
+            tracer.entries().cloned().collect::<Vec<_>>()
+        });
+
+        // Act
+        let result = compare_traces(&traces[0], &traces[1], 2);
+        let identical = compare_traces(&traces[0], &traces[0], 2);
+
+        // Assert
+        let divergence = result.unwrap();
+        assert_eq!(divergence.cycle, 3);
+        assert_eq!(divergence.location, "PC");
+        assert_eq!(divergence.context.len(), 3);
+        assert_eq!(identical, None);
+    }
+    #[wasm_bindgen_test]
+    fn test_compare_traces_length() {
+        // Arrange
+        let rom = [0x60, 0x04, 0x61, 0x08];
+        let mut emulator = emulator_with_rom(Compatibility::Original, &rom);
+        let mut tracer = Tracer::init();
+        tracer.start(Tracer::DEFAULT_CAPACITY);
+        tracer.cycle(&mut emulator.processor);
+        tracer.cycle(&mut emulator.processor);
+        let entries: Vec<_> = tracer.entries().cloned().collect();
+
+        // Act
+        let result = compare_traces(&entries, &entries[..1], 0);
+
+        // Assert
+        let divergence = result.unwrap();
+        assert_eq!(divergence.location, "trace length");
+        assert_eq!(
+            (divergence.left, divergence.right),
+            ("2".into(), "1".into())
+        );
+    }
+}
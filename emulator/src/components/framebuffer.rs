@@ -0,0 +1,204 @@
+use super::screen::Screen;
+
+/**
+ * Display pixels packed one bit each, a `u64` per row with the leftmost pixel in the highest bit.
+ *
+ * Only the 64x32 display is supported, `Platform::Chip8Hires` moves the load address but its 64x64 mode isn't emulated.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Framebuffer {
+    rows: [u64; Screen::HEIGHT],
+}
+// A row has to fit a u64 exactly, wider displays need another layout
+const _: () = assert!(Screen::WIDTH == u64::BITS as usize);
+
+impl Framebuffer {
+    pub fn init() -> Framebuffer {
+        Framebuffer {
+            rows: [0; Screen::HEIGHT],
+        }
+    }
+    /** Packs a byte per pixel view, row by row, any non-zero byte is a pixel which is on */
+    pub fn from_bytes(bytes: &[u8]) -> Framebuffer {
+        let mut framebuffer = Framebuffer::init();
+        for (row, pixels) in framebuffer.rows.iter_mut().zip(bytes.chunks(Screen::WIDTH)) {
+            *row = pixels
+                .iter()
+                .enumerate()
+                .filter(|(_, &pixel)| pixel != 0)
+                .fold(0, |row, (x, _)| row | Self::mask(x));
+        }
+
+        framebuffer
+    }
+
+    fn mask(x: usize) -> u64 {
+        1 << (Screen::WIDTH - 1 - x)
+    }
+
+    pub fn rows(&self) -> &[u64; Screen::HEIGHT] {
+        &self.rows
+    }
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.rows[y] & Self::mask(x) != 0
+    }
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        match on {
+            true => self.rows[y] |= Self::mask(x),
+            false => self.rows[y] &= !Self::mask(x),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.rows = [0; Screen::HEIGHT];
+    }
+    /** XORs an 8 pixel wide sprite onto the display, clipping at the right and bottom edges, returns whether any pixel was turned off */
+    pub fn draw(&mut self, x: usize, y: usize, sprite: &[u8]) -> bool {
+        let mut collisions = 0;
+        for (row, &line) in self.rows.iter_mut().skip(y).zip(sprite) {
+            let bits = ((line as u64) << (Screen::WIDTH - 8))
+                .checked_shr(x as u32)
+                .unwrap_or(0);
+            collisions |= *row & bits;
+            *row ^= bits;
+        }
+
+        collisions != 0
+    }
+
+    fn pixels(&self) -> impl Iterator<Item = bool> + '_ {
+        self.rows
+            .iter()
+            .flat_map(|&row| (0..Screen::WIDTH).map(move |x| row & Self::mask(x) != 0))
+    }
+    /** One byte per pixel, row by row, 1 if the pixel is on */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.pixels().map(u8::from).collect()
+    }
+    /** RGBA pixels, row by row, white for pixels which are on and opaque black for the rest */
+    pub fn to_rgba(&self) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(Screen::WIDTH * Screen::HEIGHT * 4);
+        for on in self.pixels() {
+            rgba.extend_from_slice(match on {
+                false => &[0x00, 0x00, 0x00, 0xFF],
+                true => &[0xFF, 0xFF, 0xFF, 0xFF],
+            });
+        }
+
+        rgba
+    }
+}
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Framebuffer::init()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Framebuffer;
+    use crate::components::screen::Screen;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn corner_framebuffer() -> Framebuffer {
+        let mut framebuffer = Framebuffer::init();
+        framebuffer.set(0, 0, true);
+        framebuffer.set(Screen::WIDTH - 1, Screen::HEIGHT - 1, true);
+
+        framebuffer
+    }
+
+    #[wasm_bindgen_test]
+    fn test_set_get() {
+        // Arrange
+        let mut framebuffer = corner_framebuffer();
+
+        // Act
+        framebuffer.set(0, 0, false);
+        framebuffer.set(3, 1, true);
+
+        // Assert
+        assert!(!framebuffer.get(0, 0));
+        assert!(framebuffer.get(3, 1));
+        assert!(framebuffer.get(Screen::WIDTH - 1, Screen::HEIGHT - 1));
+        assert_eq!(framebuffer.rows()[1], 0x1000_0000_0000_0000);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_draw() {
+        // Arrange
+        let mut framebuffer = Framebuffer::init();
+
+        // Act
+        let collision = framebuffer.draw(4, 2, &[0b1000_0001, 0xFF]);
+
+        // Assert
+        assert!(!collision);
+        assert_eq!(framebuffer.rows()[2], 0x0810_0000_0000_0000);
+        assert_eq!(framebuffer.rows()[3], 0x0FF0_0000_0000_0000);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_draw_collision() {
+        // Arrange
+        let mut framebuffer = Framebuffer::init();
+        framebuffer.draw(0, 0, &[0b1100_0000]);
+
+        // Act
+        let collision = framebuffer.draw(1, 0, &[0b1000_0000]);
+
+        // Assert
+        assert!(collision);
+        assert_eq!(framebuffer.rows()[0], 0x8000_0000_0000_0000);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_draw_clips() {
+        // Arrange
+        let mut framebuffer = Framebuffer::init();
+
+        // Act
+        framebuffer.draw(60, Screen::HEIGHT - 1, &[0xFF, 0xFF]);
+
+        // Assert
+        assert_eq!(framebuffer.rows()[Screen::HEIGHT - 1], 0xF);
+        assert_eq!(
+            framebuffer.rows()[0],
+            0,
+            "Pixels past the edges shouldn't wrap!"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_to_bytes() {
+        // Arrange
+        let framebuffer = corner_framebuffer();
+
+        // Act
+        let result = framebuffer.to_bytes();
+
+        // Assert
+        let mut expected = vec![0; Screen::WIDTH * Screen::HEIGHT];
+        expected[0] = 1;
+        expected[Screen::WIDTH * Screen::HEIGHT - 1] = 1;
+        assert_eq!(result, expected);
+        assert_eq!(Framebuffer::from_bytes(&result), framebuffer);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_to_rgba() {
+        // Arrange
+        let framebuffer = corner_framebuffer();
+
+        // Act
+        let result = framebuffer.to_rgba();
+
+        // Assert
+        assert_eq!(result.len(), Screen::WIDTH * Screen::HEIGHT * 4);
+        assert_eq!(
+            result[..8],
+            [0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0xFF]
+        );
+        assert_eq!(result[result.len() - 4..], [0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+}
@@ -1,10 +1,3 @@
-use lazy_static::lazy_static;
-use std::sync::Mutex;
-
-lazy_static! {
-    pub static ref INSTANCE: Mutex<Keypad> = Mutex::new(Keypad::default());
-}
-
 #[derive(Default, Debug)]
 pub struct Keypad {
     current_key: u8,
@@ -27,3 +20,26 @@ impl Keypad {
         self.is_key_pressed = false;
     }
 }
+
+/** Maps a `KeyboardEvent.code` to a CHIP-8 key, using the left side of a QWERTY keyboard */
+pub fn key_from_code(code: &str) -> Option<u8> {
+    match code {
+        "Digit1" => Some(0x1),
+        "Digit2" => Some(0x2),
+        "Digit3" => Some(0x3),
+        "Digit4" => Some(0xC),
+        "KeyQ" => Some(0x4),
+        "KeyW" => Some(0x5),
+        "KeyE" => Some(0x6),
+        "KeyR" => Some(0xD),
+        "KeyA" => Some(0x7),
+        "KeyS" => Some(0x8),
+        "KeyD" => Some(0x9),
+        "KeyF" => Some(0xE),
+        "KeyZ" => Some(0xA),
+        "KeyX" => Some(0x0),
+        "KeyC" => Some(0xB),
+        "KeyV" => Some(0xF),
+        _ => None,
+    }
+}
@@ -1,8 +1,90 @@
+use crate::instruction::{decode, Instruction};
 use array_init::array_init;
+use std::fmt;
+use std::ops::Range;
+use wasm_bindgen::JsValue;
+
+#[derive(Debug, PartialEq)]
+pub enum RomError {
+    /** ROM doesn't fit between its load address and the end of memory */
+    TooLarge { size: usize, max_size: usize },
+}
+impl fmt::Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomError::TooLarge { size, max_size } => write!(
+                f,
+                "ROM is too large ({} bytes, at most {} bytes fit in memory)!",
+                size, max_size
+            ),
+        }
+    }
+}
+impl std::error::Error for RomError {}
+impl From<RomError> for JsValue {
+    fn from(err: RomError) -> JsValue {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MemoryError {
+    /** Access reaches past the end of memory */
+    OutOfBounds { address: usize, length: usize },
+}
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryError::OutOfBounds { address, length } => write!(
+                f,
+                "Accessing {} bytes at {:#06X} reaches past the end of memory!",
+                length, address
+            ),
+        }
+    }
+}
+impl std::error::Error for MemoryError {}
+impl From<MemoryError> for JsValue {
+    fn from(err: MemoryError) -> JsValue {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/** Part of the memory map, following the COSMAC VIP layout */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /** Reserved for the interpreter on original systems */
+    Interpreter,
+    Font,
+    Rom,
+    /** Where the COSMAC VIP interpreter kept its stack and work area */
+    Stack,
+    /** Where the COSMAC VIP kept its display buffer */
+    Display,
+    /** Memory not used by anything in particular */
+    Free,
+}
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Region::Interpreter => "interpreter",
+            Region::Font => "font",
+            Region::Rom => "ROM",
+            Region::Stack => "stack",
+            Region::Display => "display",
+            Region::Free => "free",
+        };
+        write!(f, "{}", name)
+    }
+}
 
 #[derive(Debug)]
 pub struct Memory {
-    pub data: [u8; 4096],
+    /** Raw contents, private so every write goes through `write` and drops stale decoded instructions */
+    data: [u8; Memory::SIZE],
+
+    /** Instructions decoded so far, indexed by their address */
+    decoded: Vec<Option<Instruction>>,
 }
 impl Memory {
     pub const FONT_SET: [u8; 80] = [
@@ -25,31 +107,189 @@ impl Memory {
     ];
     pub const FONT_BEGIN_INDEX: u16 = 0x50;
     pub const ROM_BEGIN_INDEX: u16 = 0x200;
+    pub const SIZE: usize = 4096;
+    pub const STACK_BEGIN_INDEX: u16 = 0xEA0;
+    pub const DISPLAY_BEGIN_INDEX: u16 = 0xF00;
 
     pub fn init() -> Memory {
         Memory {
             data: array_init(|_| 0),
+            decoded: vec![None; Memory::SIZE],
         }
     }
 
     pub fn load_fonts(&mut self) {
-        for (i, font) in Memory::FONT_SET.iter().enumerate() {
-            self.data[i + Memory::FONT_BEGIN_INDEX as usize] = *font;
+        for (i, &font) in Memory::FONT_SET.iter().enumerate() {
+            self.write(Memory::FONT_BEGIN_INDEX + i as u16, font);
         }
     }
-    pub fn load_rom(&mut self, rom: Vec<u8>) {
-        for (i, value) in rom.iter().enumerate() {
-            self.data[Memory::ROM_BEGIN_INDEX as usize + i] = *value;
+    /** Raw contents, change them with `write`, `write_bytes` or `fill` */
+    pub fn data(&self) -> &[u8; Memory::SIZE] {
+        &self.data
+    }
+    /** Copies the ROM into memory starting at `address`, leaving memory untouched if it doesn't fit */
+    pub fn load_rom(&mut self, rom: &[u8], address: u16) -> Result<(), RomError> {
+        let begin = address as usize;
+        let max_size = Memory::SIZE.saturating_sub(begin);
+        if rom.len() > max_size {
+            return Err(RomError::TooLarge {
+                size: rom.len(),
+                max_size,
+            });
         }
+
+        self.data[begin..begin + rom.len()].copy_from_slice(rom);
+        self.invalidate();
+        Ok(())
+    }
+
+    /** Writes a byte, dropping the decoded instructions which overlap it */
+    pub fn write(&mut self, address: u16, value: u8) {
+        let address = address as usize;
+        self.data[address] = value;
+
+        self.decoded[address] = None;
+        if address > 0 {
+            self.decoded[address - 1] = None;
+        }
+    }
+    /** Raw opcode at `address`, bytes past the end of memory read as 0 */
+    pub fn opcode(&self, address: u16) -> u16 {
+        let byte = |address: usize| self.data.get(address).copied().unwrap_or(0) as u16;
+        byte(address as usize) << 0x8 | byte(address as usize + 1)
+    }
+    /** Decoded instruction at `address`, decoding it only the first time it's requested */
+    pub fn instruction(&mut self, address: u16) -> Instruction {
+        match self.decoded.get(address as usize) {
+            Some(Some(instruction)) => *instruction,
+            _ => {
+                let instruction = decode(self.opcode(address));
+                if let Some(decoded) = self.decoded.get_mut(address as usize) {
+                    *decoded = Some(instruction);
+                }
+
+                instruction
+            }
+        }
+    }
+    /** Drops all decoded instructions */
+    fn invalidate(&mut self) {
+        self.decoded.fill(None);
     }
 
     pub fn clear(&mut self) {
         self.data = array_init(|_| 0);
+        self.invalidate();
+    }
+
+    /** Up to `length` bytes starting at `address`, cut short at the end of memory */
+    pub fn read(&self, address: u16, length: usize) -> &[u8] {
+        let begin = (address as usize).min(Memory::SIZE);
+        let end = begin.saturating_add(length).min(Memory::SIZE);
+
+        &self.data[begin..end]
+    }
+    /** Writes bytes starting at `address`, leaving memory untouched if they don't fit */
+    pub fn write_bytes(&mut self, address: u16, bytes: &[u8]) -> Result<(), MemoryError> {
+        Memory::check_bounds(address, bytes.len())?;
+
+        for (i, &byte) in bytes.iter().enumerate() {
+            self.write(address + i as u16, byte);
+        }
+        Ok(())
+    }
+    /** Sets `length` bytes starting at `address` to `value`, leaving memory untouched if they don't fit */
+    pub fn fill(&mut self, address: u16, length: usize, value: u8) -> Result<(), MemoryError> {
+        Memory::check_bounds(address, length)?;
+
+        for i in 0..length {
+            self.write(address + i as u16, value);
+        }
+        Ok(())
+    }
+    /** Addresses of every occurrence of `pattern`, overlapping ones included */
+    pub fn find(&self, pattern: &[u8]) -> Vec<u16> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+
+        self.data
+            .windows(pattern.len())
+            .enumerate()
+            .filter(|(_, window)| *window == pattern)
+            .map(|(address, _)| address as u16)
+            .collect()
+    }
+    fn check_bounds(address: u16, length: usize) -> Result<(), MemoryError> {
+        if address as usize + length > Memory::SIZE {
+            return Err(MemoryError::OutOfBounds {
+                address: address as usize,
+                length,
+            });
+        }
+        Ok(())
+    }
+
+    /** Region `address` belongs to, given the addresses the ROM was loaded to */
+    pub fn region(address: u16, rom: &Range<u16>) -> Region {
+        let font_end = Memory::FONT_BEGIN_INDEX + Memory::FONT_SET.len() as u16;
+
+        if rom.contains(&address) {
+            Region::Rom
+        } else if (Memory::FONT_BEGIN_INDEX..font_end).contains(&address) {
+            Region::Font
+        } else if address < Memory::ROM_BEGIN_INDEX {
+            Region::Interpreter
+        } else if (Memory::STACK_BEGIN_INDEX..Memory::DISPLAY_BEGIN_INDEX).contains(&address) {
+            Region::Stack
+        } else if address >= Memory::DISPLAY_BEGIN_INDEX {
+            Region::Display
+        } else {
+            Region::Free
+        }
+    }
+    /** 16 bytes per line followed by their ASCII form and the regions they belong to */
+    pub fn hexdump(&self, address: u16, length: usize, rom: &Range<u16>) -> String {
+        let begin = address as usize;
+        let bytes = self.read(address, length);
+        let mut dump = String::new();
+
+        for (line, chunk) in bytes.chunks(16).enumerate() {
+            let line_address = begin + line * 16;
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| match byte {
+                    0x20..=0x7E => byte as char,
+                    _ => '.',
+                })
+                .collect();
+
+            let mut regions: Vec<Region> = Vec::new();
+            for i in 0..chunk.len() {
+                let region = Memory::region((line_address + i) as u16, rom);
+                if !regions.contains(&region) {
+                    regions.push(region);
+                }
+            }
+            let regions: Vec<String> = regions.iter().map(Region::to_string).collect();
+
+            dump += &format!(
+                "{:#06X}  {:<47}  |{:<16}|  {}\n",
+                line_address,
+                hex.join(" "),
+                ascii,
+                regions.join(", ")
+            );
+        }
+
+        dump
     }
 }
 
 mod tests {
-    use super::Memory;
+    use super::{Memory, MemoryError, Region, RomError};
+    use crate::instruction::{decode, Instruction};
     use wasm_bindgen_test::wasm_bindgen_test;
 
     #[wasm_bindgen_test]
@@ -75,13 +315,228 @@ mod tests {
         let rom = vec![0xAB, 0xCD];
 
         // Act
-        memory.load_rom(rom.clone());
+        let result = memory.load_rom(&rom, Memory::ROM_BEGIN_INDEX);
 
         // Assert
+        assert!(result.is_ok());
         assert_eq!(
             memory.data
                 [Memory::ROM_BEGIN_INDEX as usize..(Memory::ROM_BEGIN_INDEX as usize + rom.len())],
             rom
         );
     }
+    #[wasm_bindgen_test]
+    fn test_load_rom_other_address() {
+        // Arrange
+        let mut memory = Memory::init();
+        let rom = vec![0xAB, 0xCD];
+
+        // Act
+        let result = memory.load_rom(&rom, 0x600);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(memory.data[0x600..0x602], rom);
+        assert_eq!(memory.data[Memory::ROM_BEGIN_INDEX as usize], 0x0);
+    }
+    #[wasm_bindgen_test]
+    fn test_load_rom_too_large() {
+        // Arrange
+        let mut memory = Memory::init();
+        let rom = vec![0xAB; 3585];
+
+        // Act
+        let result = memory.load_rom(&rom, Memory::ROM_BEGIN_INDEX);
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(RomError::TooLarge {
+                size: 3585,
+                max_size: 3584
+            })
+        );
+        assert!(
+            memory.data.iter().all(|&byte| byte == 0x0),
+            "Memory should be left untouched!"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_instruction() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.data[0x200] = 0x12;
+        memory.data[0x201] = 0x34;
+
+        // Act
+        let result = memory.instruction(0x200);
+
+        // Assert
+        assert_eq!(result, Instruction::Jump { nnn: 0x234 });
+    }
+    #[wasm_bindgen_test]
+    fn test_instruction_at_end() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.data[0xFFF] = 0x12;
+
+        // Act
+        let result = memory.instruction(0xFFF);
+
+        // Assert
+        assert_eq!(memory.opcode(0xFFF), 0x1200);
+        assert_eq!(result, Instruction::Jump { nnn: 0x200 });
+    }
+    #[wasm_bindgen_test]
+    fn test_write_invalidates_instruction() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.data[0x200] = 0x12;
+        memory.data[0x201] = 0x34;
+        memory.instruction(0x200);
+
+        // Act
+        memory.write(0x201, 0x56);
+
+        // Assert
+        assert_eq!(memory.instruction(0x200), Instruction::Jump { nnn: 0x256 });
+    }
+    #[wasm_bindgen_test]
+    fn test_load_fonts_invalidates_instruction() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.instruction(Memory::FONT_BEGIN_INDEX);
+
+        // Act
+        memory.load_fonts();
+
+        // Assert
+        assert_eq!(
+            memory.instruction(Memory::FONT_BEGIN_INDEX),
+            decode(0xF090),
+            "Fonts should replace the decoded instruction!"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_read() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.data[0xFFE] = 0xAB;
+        memory.data[0xFFF] = 0xCD;
+
+        // Act
+        let result = memory.read(0xFFE, 4);
+
+        // Assert
+        assert_eq!(
+            result,
+            [0xAB, 0xCD],
+            "Read should stop at the end of memory!"
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_write_bytes() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.data[0x200] = 0x12;
+        memory.data[0x201] = 0x34;
+        memory.instruction(0x200);
+
+        // Act
+        let result = memory.write_bytes(0x200, &[0x61, 0x05]);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            memory.instruction(0x200),
+            Instruction::Set { x: 1, nn: 0x05 }
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_write_bytes_out_of_bounds() {
+        // Arrange
+        let mut memory = Memory::init();
+
+        // Act
+        let result = memory.write_bytes(0xFFF, &[0xAB, 0xCD]);
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(MemoryError::OutOfBounds {
+                address: 0xFFF,
+                length: 2
+            })
+        );
+        assert_eq!(memory.data[0xFFF], 0x0, "Memory should be left untouched!");
+    }
+    #[wasm_bindgen_test]
+    fn test_fill() {
+        // Arrange
+        let mut memory = Memory::init();
+
+        // Act
+        let result = memory.fill(0x300, 4, 0xAA);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            memory.data[0x2FF..0x305],
+            [0x00, 0xAA, 0xAA, 0xAA, 0xAA, 0x00]
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_find() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.data[0x300..0x303].copy_from_slice(&[0xAA, 0xAA, 0xAA]);
+
+        // Act
+        let result = memory.find(&[0xAA, 0xAA]);
+
+        // Assert
+        assert_eq!(result, [0x300, 0x301]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_region() {
+        // Arrange
+        let rom = 0x200..0x300;
+
+        // Act
+        let results =
+            [0x000, 0x050, 0x200, 0x300, 0xEA0, 0xF00].map(|address| Memory::region(address, &rom));
+
+        // Assert
+        assert_eq!(
+            results,
+            [
+                Region::Interpreter,
+                Region::Font,
+                Region::Rom,
+                Region::Free,
+                Region::Stack,
+                Region::Display
+            ]
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_hexdump() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.load_fonts();
+        memory.data[0x200..0x202].copy_from_slice(b"Hi");
+
+        // Act
+        let result = memory.hexdump(0x1F8, 16, &(0x200..0x202));
+
+        // Assert
+        assert_eq!(
+            result,
+            "0x01F8  00 00 00 00 00 00 00 00 48 69 00 00 00 00 00 00  |........Hi......|  \
+             interpreter, ROM, free\n"
+        );
+    }
 }
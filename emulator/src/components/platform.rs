@@ -0,0 +1,79 @@
+use super::memory::Memory;
+use std::fmt;
+use std::str::FromStr;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/** Machine the ROM was written for, decides the memory layout */
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /** COSMAC VIP CHIP-8, also used by SCHIP and XO-CHIP programs */
+    Chip8,
+    /** Hi-res CHIP-8 variant for the COSMAC VIP, only its load address, the display stays 64x32 */
+    Chip8Hires,
+    /** CHIP-8 for the ETI-660 computer */
+    Eti660,
+}
+impl Platform {
+    /** Address the ROM is loaded at and execution starts from */
+    pub fn load_address(&self) -> u16 {
+        match self {
+            Platform::Chip8 => Memory::ROM_BEGIN_INDEX,
+            Platform::Chip8Hires => 0x2C0,
+            Platform::Eti660 => 0x600,
+        }
+    }
+}
+/** Parses the names the command line tools use, `chip8`, `chip8-hires` or `eti660` */
+impl FromStr for Platform {
+    type Err = ParsePlatformError;
+
+    fn from_str(name: &str) -> Result<Platform, ParsePlatformError> {
+        match name {
+            "chip8" => Ok(Platform::Chip8),
+            "chip8-hires" => Ok(Platform::Chip8Hires),
+            "eti660" => Ok(Platform::Eti660),
+            _ => Err(ParsePlatformError(name.to_string())),
+        }
+    }
+}
+
+/** Name which isn't a known platform */
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsePlatformError(pub String);
+impl fmt::Display for ParsePlatformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown platform {}, expected chip8, chip8-hires or eti660",
+            self.0
+        )
+    }
+}
+impl std::error::Error for ParsePlatformError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParsePlatformError, Platform};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_from_str() {
+        // Arrange
+        let names = ["chip8", "chip8-hires", "eti660", "schip"];
+
+        // Act
+        let results = names.map(str::parse::<Platform>);
+
+        // Assert
+        assert_eq!(
+            results,
+            [
+                Ok(Platform::Chip8),
+                Ok(Platform::Chip8Hires),
+                Ok(Platform::Eti660),
+                Err(ParsePlatformError("schip".to_string()))
+            ]
+        );
+    }
+}
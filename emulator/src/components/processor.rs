@@ -1,17 +1,53 @@
+use super::framebuffer::Framebuffer;
+use super::keypad::Keypad;
 use super::memory::Memory;
-use super::screen::Screen;
+use super::stack::Stack;
+use crate::breakpoints::Breakpoints;
+use crate::fault::Fault;
+use crate::heatmap::Heatmap;
+use crate::instruction::{decode, Instruction};
 use crate::opcodes::*;
+use crate::profiler::Profiler;
 use array_init::array_init;
 use log::*;
+use rand::{rngs::StdRng, SeedableRng};
+use std::fmt;
+use std::str::FromStr;
 use wasm_bindgen::prelude::wasm_bindgen;
 use web_sys::{window, HtmlAudioElement};
 
 #[wasm_bindgen]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Compatibility {
     Original,
     New,
 }
+/** Parses the names the command line tools use, `original` or `new` */
+impl FromStr for Compatibility {
+    type Err = ParseCompatibilityError;
+
+    fn from_str(name: &str) -> Result<Compatibility, ParseCompatibilityError> {
+        match name {
+            "original" => Ok(Compatibility::Original),
+            "new" => Ok(Compatibility::New),
+            _ => Err(ParseCompatibilityError(name.to_string())),
+        }
+    }
+}
+
+/** Name which isn't a known compatibility */
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseCompatibilityError(pub String);
+impl fmt::Display for ParseCompatibilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown compatibility {}, expected original or new",
+            self.0
+        )
+    }
+}
+impl std::error::Error for ParseCompatibilityError {}
 
 #[derive(Debug)]
 pub struct Processor {
@@ -22,7 +58,7 @@ pub struct Processor {
     pub i: u16,
 
     /** A stack for 16-bit addresses, which is used to call subroutines/functions and return from them */
-    pub stack: Vec<u16>,
+    pub stack: Stack,
 
     /** Delay timer - 8-bit value which is decremented at a rate of 60 Hz (60 times per second) until it reaches 0 */
     pub delay_timer: u8,
@@ -39,19 +75,47 @@ pub struct Processor {
 
     pub compatibility: Compatibility,
     pub memory: Memory,
-    pub gfx: [u8; Screen::WIDTH * Screen::HEIGHT],
+    pub keypad: Keypad,
+
+    /** Source of CXNN random numbers, seed it for reproducible runs */
+    pub rng: StdRng,
+
+    pub gfx: Framebuffer,
+
+    /** Set when the program hits a fatal fault, the processor stops executing until reset */
+    pub fault: Option<Fault>,
+
+    /** Number of cycles executed since the last reset */
+    pub cycles: u64,
+
+    /** Collects execution statistics when set, costs a bit of speed */
+    pub profiler: Option<Profiler>,
+    /** Tracks recent memory accesses when set */
+    pub heatmap: Option<Heatmap>,
+
+    /** Height of the last sprite DXYN drew from each address since the last reset */
+    pub drawn_sprites: Vec<Option<u8>>,
+
+    pub breakpoints: Breakpoints,
+    /** Address of the breakpoint which stopped the last `cycle` or `run`, if any */
+    pub hit_breakpoint: Option<u16>,
+    /** Breakpoint to step over once, so execution can continue after stopping at it */
+    resume_at: Option<u16>,
 }
 impl Processor {
+    /** Cycles per tick of the 60 Hz timers, which is also how often the display gets drawn */
+    pub const CYCLES_PER_FRAME: u32 = 10;
+
     /** Initializes with compatibility for original systems */
     pub fn init() -> Processor {
         let processor = Processor {
             pc: Memory::ROM_BEGIN_INDEX,
             i: 0,
-            stack: Vec::new(),
+            stack: Stack::init(),
             delay_timer: 0,
             sound_timer: 0,
-            // For tests, which run in Node.js, we don't have a window object
-            audio_element: if window().is_some() {
+            // For tests, which run in Node.js, and native builds we don't have a window object
+            audio_element: if cfg!(target_arch = "wasm32") && window().is_some() {
                 Option::Some(HtmlAudioElement::new().expect("Failed to create audio element!"))
             } else {
                 Option::None
@@ -60,10 +124,20 @@ impl Processor {
             v: array_init(|_| 0),
             compatibility: Compatibility::Original,
             memory: Memory::init(),
-            gfx: array_init(|_| 0),
+            keypad: Keypad::default(),
+            rng: StdRng::from_entropy(),
+            gfx: Framebuffer::init(),
+            fault: None,
+            cycles: 0,
+            profiler: None,
+            heatmap: None,
+            drawn_sprites: vec![None; Memory::SIZE],
+            breakpoints: Breakpoints::init(),
+            hit_breakpoint: None,
+            resume_at: None,
         };
-        if processor.audio_element.is_some() {
-            processor.audio_element.as_ref().unwrap().set_src("data:audio/wav;base64,//uQRAAAAWMSLwUIYAAsYkXgoQwAEaYLWfkWgAI0wWs/ItAAAGDgYtAgAyN+QWaAAihwMWm4G8QQRDiMcCBcH3Cc+CDv/7xA4Tvh9Rz/y8QADBwMWgQAZG/ILNAARQ4GLTcDeIIIhxGOBAuD7hOfBB3/94gcJ3w+o5/5eIAIAAAVwWgQAVQ2ORaIQwEMAJiDg95G4nQL7mQVWI6GwRcfsZAcsKkJvxgxEjzFUgfHoSQ9Qq7KNwqHwuB13MA4a1q/DmBrHgPcmjiGoh//EwC5nGPEmS4RcfkVKOhJf+WOgoxJclFz3kgn//dBA+ya1GhurNn8zb//9NNutNuhz31f////9vt///z+IdAEAAAK4LQIAKobHItEIYCGAExBwe8jcToF9zIKrEdDYIuP2MgOWFSE34wYiR5iqQPj0JIeoVdlG4VD4XA67mAcNa1fhzA1jwHuTRxDUQ//iYBczjHiTJcIuPyKlHQkv/LHQUYkuSi57yQT//uggfZNajQ3Vmz+Zt//+mm3Wm3Q576v////+32///5/EOgAAADVghQAAAAA//uQZAUAB1WI0PZugAAAAAoQwAAAEk3nRd2qAAAAACiDgAAAAAAABCqEEQRLCgwpBGMlJkIz8jKhGvj4k6jzRnqasNKIeoh5gI7BJaC1A1AoNBjJgbyApVS4IDlZgDU5WUAxEKDNmmALHzZp0Fkz1FMTmGFl1FMEyodIavcCAUHDWrKAIA4aa2oCgILEBupZgHvAhEBcZ6joQBxS76AgccrFlczBvKLC0QI2cBoCFvfTDAo7eoOQInqDPBtvrDEZBNYN5xwNwxQRfw8ZQ5wQVLvO8OYU+mHvFLlDh05Mdg7BT6YrRPpCBznMB2r//xKJjyyOh+cImr2/4doscwD6neZjuZR4AgAABYAAAABy1xcdQtxYBYYZdifkUDgzzXaXn98Z0oi9ILU5mBjFANmRwlVJ3/6jYDAmxaiDG3/6xjQQCCKkRb/6kg/wW+kSJ5//rLobkLSiKmqP/0ikJuDaSaSf/6JiLYLEYnW/+kXg1WRVJL/9EmQ1YZIsv/6Qzwy5qk7/+tEU0nkls3/zIUMPKNX/6yZLf+kFgAfgGyLFAUwY//uQZAUABcd5UiNPVXAAAApAAAAAE0VZQKw9ISAAACgAAAAAVQIygIElVrFkBS+Jhi+EAuu+lKAkYUEIsmEAEoMeDmCETMvfSHTGkF5RWH7kz/ESHWPAq/kcCRhqBtMdokPdM7vil7RG98A2sc7zO6ZvTdM7pmOUAZTnJW+NXxqmd41dqJ6mLTXxrPpnV8avaIf5SvL7pndPvPpndJR9Kuu8fePvuiuhorgWjp7Mf/PRjxcFCPDkW31srioCExivv9lcwKEaHsf/7ow2Fl1T/9RkXgEhYElAoCLFtMArxwivDJJ+bR1HTKJdlEoTELCIqgEwVGSQ+hIm0NbK8WXcTEI0UPoa2NbG4y2K00JEWbZavJXkYaqo9CRHS55FcZTjKEk3NKoCYUnSQ0rWxrZbFKbKIhOKPZe1cJKzZSaQrIyULHDZmV5K4xySsDRKWOruanGtjLJXFEmwaIbDLX0hIPBUQPVFVkQkDoUNfSoDgQGKPekoxeGzA4DUvnn4bxzcZrtJyipKfPNy5w+9lnXwgqsiyHNeSVpemw4bWb9psYeq//uQZBoABQt4yMVxYAIAAAkQoAAAHvYpL5m6AAgAACXDAAAAD59jblTirQe9upFsmZbpMudy7Lz1X1DYsxOOSWpfPqNX2WqktK0DMvuGwlbNj44TleLPQ+Gsfb+GOWOKJoIrWb3cIMeeON6lz2umTqMXV8Mj30yWPpjoSa9ujK8SyeJP5y5mOW1D6hvLepeveEAEDo0mgCRClOEgANv3B9a6fikgUSu/DmAMATrGx7nng5p5iimPNZsfQLYB2sDLIkzRKZOHGAaUyDcpFBSLG9MCQALgAIgQs2YunOszLSAyQYPVC2YdGGeHD2dTdJk1pAHGAWDjnkcLKFymS3RQZTInzySoBwMG0QueC3gMsCEYxUqlrcxK6k1LQQcsmyYeQPdC2YfuGPASCBkcVMQQqpVJshui1tkXQJQV0OXGAZMXSOEEBRirXbVRQW7ugq7IM7rPWSZyDlM3IuNEkxzCOJ0ny2ThNkyRai1b6ev//3dzNGzNb//4uAvHT5sURcZCFcuKLhOFs8mLAAEAt4UWAAIABAAAAAB4qbHo0tIjVkUU//uQZAwABfSFz3ZqQAAAAAngwAAAE1HjMp2qAAAAACZDgAAAD5UkTE1UgZEUExqYynN1qZvqIOREEFmBcJQkwdxiFtw0qEOkGYfRDifBui9MQg4QAHAqWtAWHoCxu1Yf4VfWLPIM2mHDFsbQEVGwyqQoQcwnfHeIkNt9YnkiaS1oizycqJrx4KOQjahZxWbcZgztj2c49nKmkId44S71j0c8eV9yDK6uPRzx5X18eDvjvQ6yKo9ZSS6l//8elePK/Lf//IInrOF/FvDoADYAGBMGb7FtErm5MXMlmPAJQVgWta7Zx2go+8xJ0UiCb8LHHdftWyLJE0QIAIsI+UbXu67dZMjmgDGCGl1H+vpF4NSDckSIkk7Vd+sxEhBQMRU8j/12UIRhzSaUdQ+rQU5kGeFxm+hb1oh6pWWmv3uvmReDl0UnvtapVaIzo1jZbf/pD6ElLqSX+rUmOQNpJFa/r+sa4e/pBlAABoAAAAA3CUgShLdGIxsY7AUABPRrgCABdDuQ5GC7DqPQCgbbJUAoRSUj+NIEig0YfyWUho1VBBBA//uQZB4ABZx5zfMakeAAAAmwAAAAF5F3P0w9GtAAACfAAAAAwLhMDmAYWMgVEG1U0FIGCBgXBXAtfMH10000EEEEEECUBYln03TTTdNBDZopopYvrTTdNa325mImNg3TTPV9q3pmY0xoO6bv3r00y+IDGid/9aaaZTGMuj9mpu9Mpio1dXrr5HERTZSmqU36A3CumzN/9Robv/Xx4v9ijkSRSNLQhAWumap82WRSBUqXStV/YcS+XVLnSS+WLDroqArFkMEsAS+eWmrUzrO0oEmE40RlMZ5+ODIkAyKAGUwZ3mVKmcamcJnMW26MRPgUw6j+LkhyHGVGYjSUUKNpuJUQoOIAyDvEyG8S5yfK6dhZc0Tx1KI/gviKL6qvvFs1+bWtaz58uUNnryq6kt5RzOCkPWlVqVX2a/EEBUdU1KrXLf40GoiiFXK///qpoiDXrOgqDR38JB0bw7SoL+ZB9o1RCkQjQ2CBYZKd/+VJxZRRZlqSkKiws0WFxUyCwsKiMy7hUVFhIaCrNQsKkTIsLivwKKigsj8XYlwt/WKi2N4d//uQRCSAAjURNIHpMZBGYiaQPSYyAAABLAAAAAAAACWAAAAApUF/Mg+0aohSIRobBAsMlO//Kk4soosy1JSFRYWaLC4qZBYWFRGZdwqKiwkNBVmoWFSJkWFxX4FFRQWR+LsS4W/rFRb/////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////VEFHAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAU291bmRib3kuZGUAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAMjAwNGh0dHA6Ly93d3cuc291bmRib3kuZGUAAAAAAAAAACU=");
+        if let Some(audio_element) = &processor.audio_element {
+            audio_element.set_src("data:audio/wav;base64,//uQRAAAAWMSLwUIYAAsYkXgoQwAEaYLWfkWgAI0wWs/ItAAAGDgYtAgAyN+QWaAAihwMWm4G8QQRDiMcCBcH3Cc+CDv/7xA4Tvh9Rz/y8QADBwMWgQAZG/ILNAARQ4GLTcDeIIIhxGOBAuD7hOfBB3/94gcJ3w+o5/5eIAIAAAVwWgQAVQ2ORaIQwEMAJiDg95G4nQL7mQVWI6GwRcfsZAcsKkJvxgxEjzFUgfHoSQ9Qq7KNwqHwuB13MA4a1q/DmBrHgPcmjiGoh//EwC5nGPEmS4RcfkVKOhJf+WOgoxJclFz3kgn//dBA+ya1GhurNn8zb//9NNutNuhz31f////9vt///z+IdAEAAAK4LQIAKobHItEIYCGAExBwe8jcToF9zIKrEdDYIuP2MgOWFSE34wYiR5iqQPj0JIeoVdlG4VD4XA67mAcNa1fhzA1jwHuTRxDUQ//iYBczjHiTJcIuPyKlHQkv/LHQUYkuSi57yQT//uggfZNajQ3Vmz+Zt//+mm3Wm3Q576v////+32///5/EOgAAADVghQAAAAA//uQZAUAB1WI0PZugAAAAAoQwAAAEk3nRd2qAAAAACiDgAAAAAAABCqEEQRLCgwpBGMlJkIz8jKhGvj4k6jzRnqasNKIeoh5gI7BJaC1A1AoNBjJgbyApVS4IDlZgDU5WUAxEKDNmmALHzZp0Fkz1FMTmGFl1FMEyodIavcCAUHDWrKAIA4aa2oCgILEBupZgHvAhEBcZ6joQBxS76AgccrFlczBvKLC0QI2cBoCFvfTDAo7eoOQInqDPBtvrDEZBNYN5xwNwxQRfw8ZQ5wQVLvO8OYU+mHvFLlDh05Mdg7BT6YrRPpCBznMB2r//xKJjyyOh+cImr2/4doscwD6neZjuZR4AgAABYAAAABy1xcdQtxYBYYZdifkUDgzzXaXn98Z0oi9ILU5mBjFANmRwlVJ3/6jYDAmxaiDG3/6xjQQCCKkRb/6kg/wW+kSJ5//rLobkLSiKmqP/0ikJuDaSaSf/6JiLYLEYnW/+kXg1WRVJL/9EmQ1YZIsv/6Qzwy5qk7/+tEU0nkls3/zIUMPKNX/6yZLf+kFgAfgGyLFAUwY//uQZAUABcd5UiNPVXAAAApAAAAAE0VZQKw9ISAAACgAAAAAVQIygIElVrFkBS+Jhi+EAuu+lKAkYUEIsmEAEoMeDmCETMvfSHTGkF5RWH7kz/ESHWPAq/kcCRhqBtMdokPdM7vil7RG98A2sc7zO6ZvTdM7pmOUAZTnJW+NXxqmd41dqJ6mLTXxrPpnV8avaIf5SvL7pndPvPpndJR9Kuu8fePvuiuhorgWjp7Mf/PRjxcFCPDkW31srioCExivv9lcwKEaHsf/7ow2Fl1T/9RkXgEhYElAoCLFtMArxwivDJJ+bR1HTKJdlEoTELCIqgEwVGSQ+hIm0NbK8WXcTEI0UPoa2NbG4y2K00JEWbZavJXkYaqo9CRHS55FcZTjKEk3NKoCYUnSQ0rWxrZbFKbKIhOKPZe1cJKzZSaQrIyULHDZmV5K4xySsDRKWOruanGtjLJXFEmwaIbDLX0hIPBUQPVFVkQkDoUNfSoDgQGKPekoxeGzA4DUvnn4bxzcZrtJyipKfPNy5w+9lnXwgqsiyHNeSVpemw4bWb9psYeq//uQZBoABQt4yMVxYAIAAAkQoAAAHvYpL5m6AAgAACXDAAAAD59jblTirQe9upFsmZbpMudy7Lz1X1DYsxOOSWpfPqNX2WqktK0DMvuGwlbNj44TleLPQ+Gsfb+GOWOKJoIrWb3cIMeeON6lz2umTqMXV8Mj30yWPpjoSa9ujK8SyeJP5y5mOW1D6hvLepeveEAEDo0mgCRClOEgANv3B9a6fikgUSu/DmAMATrGx7nng5p5iimPNZsfQLYB2sDLIkzRKZOHGAaUyDcpFBSLG9MCQALgAIgQs2YunOszLSAyQYPVC2YdGGeHD2dTdJk1pAHGAWDjnkcLKFymS3RQZTInzySoBwMG0QueC3gMsCEYxUqlrcxK6k1LQQcsmyYeQPdC2YfuGPASCBkcVMQQqpVJshui1tkXQJQV0OXGAZMXSOEEBRirXbVRQW7ugq7IM7rPWSZyDlM3IuNEkxzCOJ0ny2ThNkyRai1b6ev//3dzNGzNb//4uAvHT5sURcZCFcuKLhOFs8mLAAEAt4UWAAIABAAAAAB4qbHo0tIjVkUU//uQZAwABfSFz3ZqQAAAAAngwAAAE1HjMp2qAAAAACZDgAAAD5UkTE1UgZEUExqYynN1qZvqIOREEFmBcJQkwdxiFtw0qEOkGYfRDifBui9MQg4QAHAqWtAWHoCxu1Yf4VfWLPIM2mHDFsbQEVGwyqQoQcwnfHeIkNt9YnkiaS1oizycqJrx4KOQjahZxWbcZgztj2c49nKmkId44S71j0c8eV9yDK6uPRzx5X18eDvjvQ6yKo9ZSS6l//8elePK/Lf//IInrOF/FvDoADYAGBMGb7FtErm5MXMlmPAJQVgWta7Zx2go+8xJ0UiCb8LHHdftWyLJE0QIAIsI+UbXu67dZMjmgDGCGl1H+vpF4NSDckSIkk7Vd+sxEhBQMRU8j/12UIRhzSaUdQ+rQU5kGeFxm+hb1oh6pWWmv3uvmReDl0UnvtapVaIzo1jZbf/pD6ElLqSX+rUmOQNpJFa/r+sa4e/pBlAABoAAAAA3CUgShLdGIxsY7AUABPRrgCABdDuQ5GC7DqPQCgbbJUAoRSUj+NIEig0YfyWUho1VBBBA//uQZB4ABZx5zfMakeAAAAmwAAAAF5F3P0w9GtAAACfAAAAAwLhMDmAYWMgVEG1U0FIGCBgXBXAtfMH10000EEEEEECUBYln03TTTdNBDZopopYvrTTdNa325mImNg3TTPV9q3pmY0xoO6bv3r00y+IDGid/9aaaZTGMuj9mpu9Mpio1dXrr5HERTZSmqU36A3CumzN/9Robv/Xx4v9ijkSRSNLQhAWumap82WRSBUqXStV/YcS+XVLnSS+WLDroqArFkMEsAS+eWmrUzrO0oEmE40RlMZ5+ODIkAyKAGUwZ3mVKmcamcJnMW26MRPgUw6j+LkhyHGVGYjSUUKNpuJUQoOIAyDvEyG8S5yfK6dhZc0Tx1KI/gviKL6qvvFs1+bWtaz58uUNnryq6kt5RzOCkPWlVqVX2a/EEBUdU1KrXLf40GoiiFXK///qpoiDXrOgqDR38JB0bw7SoL+ZB9o1RCkQjQ2CBYZKd/+VJxZRRZlqSkKiws0WFxUyCwsKiMy7hUVFhIaCrNQsKkTIsLivwKKigsj8XYlwt/WKi2N4d//uQRCSAAjURNIHpMZBGYiaQPSYyAAABLAAAAAAAACWAAAAApUF/Mg+0aohSIRobBAsMlO//Kk4soosy1JSFRYWaLC4qZBYWFRGZdwqKiwkNBVmoWFSJkWFxX4FFRQWR+LsS4W/rFRb/////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////VEFHAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAU291bmRib3kuZGUAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAMjAwNGh0dHA6Ly93d3cuc291bmRib3kuZGUAAAAAAAAAACU=");
         }
 
         processor
@@ -89,28 +163,141 @@ impl Processor {
         }
     }
 
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /** Clears registers, timers, stack, display and faults and jumps to `pc`, memory is kept */
+    pub fn reset(&mut self, pc: u16) {
+        self.pc = pc;
+        self.i = 0;
+        self.v = array_init(|_| 0);
+        self.stack.clear();
+        self.delay_timer = 0;
+        self.sound_timer = 0;
+        self.timer_subtract = 0.0;
+        self.gfx.clear();
+        self.fault = None;
+        self.cycles = 0;
+        self.drawn_sprites.fill(None);
+        self.hit_breakpoint = None;
+        self.resume_at = None;
+    }
+
+    /** Executes one instruction, unless there's a fatal fault or a breakpoint stops before it */
     pub fn cycle(&mut self) {
+        self.hit_breakpoint = None;
+        if self.fault.is_some() || self.breakpoints.is_armed(self.pc) && self.check_breakpoint() {
+            return;
+        }
         debug!("==========================");
 
-        let instruction = self.fetch();
-        self.pc += 2;
-        debug!("Instruction: {:#06X}, PC: {:#06X}", instruction, self.pc);
+        let opcode = self.fetch();
+        let instruction = decode(opcode);
+        debug!("{:#06X}: {:#06X} {}", self.pc, opcode, instruction);
+
+        self.step(instruction);
+    }
+    /**
+     * Runs up to `cycles` cycles without logging, using instructions decoded by previous runs.
+     * Stops early on a fatal fault or a breakpoint, returns the number of cycles run.
+     */
+    pub fn run(&mut self, cycles: u32) -> u32 {
+        self.hit_breakpoint = None;
+        for cycle in 0..cycles {
+            if self.fault.is_some() || self.breakpoints.is_armed(self.pc) && self.check_breakpoint()
+            {
+                return cycle;
+            }
 
-        let (first, rest) = self.decode(instruction);
+            let instruction = self.memory.instruction(self.pc);
+            self.step(instruction);
+        }
+
+        cycles
+    }
+    fn step(&mut self, instruction: Instruction) {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.record(self.pc, &instruction, self.i);
+        }
+        if let Some(heatmap) = &mut self.heatmap {
+            heatmap.record(self.pc, &instruction, self.i);
+        }
 
-        self.execute(first, rest).unwrap_or_else(|err| {
-            warn!("{}", err);
-        });
+        let pc = self.pc;
+        self.pc += 2;
+        self.cycles += 1;
+        self.resume_at = None;
+
+        let result = match self.execute(instruction) {
+            Err(fault) if fault.is_fatal() => Err(fault),
+            result => {
+                if let Err(fault) = result {
+                    warn!("{}", fault);
+                }
+                // The last instruction starts at 0xFFE, anything further would be fetched past the end
+                match self.pc as usize >= Memory::SIZE - 1 {
+                    true => Err(Fault::PcOutOfBounds(self.pc)),
+                    false => Ok(()),
+                }
+            }
+        };
+        if let Err(fault) = result {
+            // Leave PC pointing at the faulting instruction
+            self.pc = pc;
+            error!("{} PC: {:#06X}", fault, self.pc);
+            self.fault = Some(fault);
+        }
 
         self.update_timers();
     }
-    fn update_timers(&mut self) {
-        if self.audio_element.is_none() {
-            return;
+    /** Makes the next `cycle` or `run` execute the instruction at PC even if there's a breakpoint on it */
+    pub fn resume(&mut self) {
+        self.resume_at = Some(self.pc);
+    }
+    /**
+     * Counts a hit of the breakpoint at PC and logs its message if it's a logpoint,
+     * returns whether execution should stop before the instruction
+     */
+    fn check_breakpoint(&mut self) -> bool {
+        if self.resume_at == Some(self.pc) {
+            return false;
+        }
+        let Some(hit_count) = self.breakpoints.count_hit(self.pc) else {
+            return false;
+        };
+
+        let Some(breakpoint) = self.breakpoints.get(self.pc) else {
+            return false;
+        };
+        if breakpoint
+            .condition
+            .as_ref()
+            .is_some_and(|condition| !condition.is_met(self, hit_count))
+        {
+            return false;
         }
 
+        match breakpoint
+            .log_message
+            .as_ref()
+            .map(|message| message.format(self, hit_count))
+        {
+            Some(message) => {
+                info!("{:#06X}: {}", self.pc, message);
+                self.breakpoints.log(message);
+                false
+            }
+            None => {
+                self.hit_breakpoint = Some(self.pc);
+                self.resume_at = Some(self.pc);
+                true
+            }
+        }
+    }
+    fn update_timers(&mut self) {
         // We can't do this in a separate thread so we do it this way
-        self.timer_subtract += 1.0 / 10.0;
+        self.timer_subtract += 1.0 / Processor::CYCLES_PER_FRAME as f32;
 
         if self.timer_subtract >= 1.0 {
             if self.delay_timer > 0 {
@@ -118,11 +305,9 @@ impl Processor {
             }
             #[allow(unused_must_use)]
             if self.sound_timer > 0 {
-                self.audio_element
-                    .as_ref()
-                    .unwrap()
-                    .play()
-                    .expect("Failed to play audio!");
+                if let Some(audio_element) = &self.audio_element {
+                    audio_element.play().expect("Failed to play audio!");
+                }
                 self.sound_timer -= 1;
             }
 
@@ -130,373 +315,89 @@ impl Processor {
         }
     }
 
-    fn fetch(&self) -> u16 {
-        let first_half = self.memory.data[self.pc as usize] as u16;
-        let second_half = self.memory.data[self.pc as usize + 1] as u16;
-
-        (first_half) << 0x8 | second_half
-    }
-    fn decode(&self, instruction: u16) -> (u16, u16) {
-        let first = (instruction & 0xF000) >> 0xC;
-        let rest = instruction & 0x0FFF;
-
-        (first, rest)
+    pub fn fetch(&self) -> u16 {
+        self.memory.opcode(self.pc)
     }
-    pub fn execute(&mut self, first: u16, rest: u16) -> Result<(), Box<dyn std::error::Error>> {
-        let mut not_found = false;
-
-        match first {
-            0x0 => match rest {
-                0x0E0 => {
-                    OpCode00E0::execute(self, &[]);
-
-                    debug!("Clear screen");
-                }
-                0x0EE => {
-                    OpCode00EE::execute(self, &[]);
-
-                    debug!("Return from subroutine");
-                }
-                _ => {
-                    not_found = true;
-                }
-            },
-            0x1 => {
-                let nnn = rest;
-                OpCode1NNN::execute(self, &[nnn]);
-
-                debug!("Jump to {:#06X} -> {:#06X}", rest, self.pc);
-            }
-            0x2 => {
-                let nnn = rest;
-                OpCode2NNN::execute(self, &[nnn]);
-
-                debug!(
-                    "Call subroutine at {:#06X} -> stack[0]={:#06X}",
-                    rest, self.stack[0]
-                );
-            }
-            0x3 => {
-                let x = (rest & 0xF00) >> 8;
-                let nn = rest & 0x0FF;
-                OpCode3XNN::execute(self, &[x, nn]);
-
-                debug!(
-                    "Skip next instruction if V{:X} ({:#06X}) == {:#06X}",
-                    x, self.v[x as usize], nn
-                );
-            }
-            0x4 => {
-                let x = (rest & 0xF00) >> 8;
-                let nn = rest & 0x0FF;
-                OpCode4XNN::execute(self, &[x, nn]);
-
-                debug!(
-                    "Skip next instruction if V{:X} ({:#06X}) != {:#06X}",
-                    x, self.v[x as usize], nn
-                );
-            }
-            0x5 => {
-                let x = (rest & 0xF00) >> 8;
-                let y = (rest & 0x0F0) >> 4;
-                OpCode5XY0::execute(self, &[x, y]);
-
-                debug!(
-                    "Skip next instruction if V{:X} ({:#06X}) == V{:X} ({:#06X})",
-                    x, self.v[x as usize], y, self.v[y as usize]
-                );
-            }
-            0x6 => {
-                let x = (rest & 0xF00) >> 8;
-                let nn = rest & 0x0FF;
-                OpCode6XNN::execute(self, &[x, nn]);
-
-                debug!("Set V{:X} to {:#06X} -> {:#06X}", x, nn, self.v[x as usize]);
-            }
-            0x7 => {
-                let x = (rest & 0xF00) >> 8;
-                let nn = rest & 0x0FF;
-                OpCode7XNN::execute(self, &[x, nn]);
-
-                debug!("Add {:#06X} to V{:X} -> {:#06X}", nn, x, self.v[x as usize]);
-            }
-            0x8 => match rest & 0x00F {
-                0x0 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY0::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} ({:#06X}) -> {:#06X}",
-                        x, y, self.v[y as usize], self.v[x as usize]
-                    );
-                }
-                0x1 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY1::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} | V{:X} -> {:#06X}",
-                        x, x, y, self.v[y as usize]
-                    );
-                }
-                0x2 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY2::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} & V{:X} -> {:#06X}",
-                        x, x, y, self.v[y as usize]
-                    );
-                }
-                0x3 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY3::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} ^ V{:X} -> {:#06X}",
-                        x, x, y, self.v[y as usize]
-                    );
-                }
-                0x4 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY4::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} + V{:X} -> {:#06X}",
-                        x, x, y, self.v[x as usize]
-                    );
-                }
-                0x5 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY5::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} - V{:X} -> {:#06X}",
-                        x, x, y, self.v[x as usize]
-                    );
-                }
-                0x6 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY6::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} >> 1 -> {:#06X}",
-                        x, y, self.v[x as usize]
-                    );
-                }
-                0x7 => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XY7::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} - V{:X} -> {:#06X}",
-                        x, y, x, self.v[x as usize]
-                    );
-                }
-                0xE => {
-                    let x = (rest & 0xF00) >> 8;
-                    let y = (rest & 0x0F0) >> 4;
-                    OpCode8XYE::execute(self, &[x, y]);
-
-                    debug!(
-                        "Set V{:X} to V{:X} << 1 -> {:#06X}",
-                        x, y, self.v[x as usize]
-                    );
-                }
-                _ => {
-                    not_found = true;
-                }
-            },
-            0x9 => {
-                let x = (rest & 0xF00) >> 8;
-                let y = (rest & 0x0F0) >> 4;
-                OpCode9XY0::execute(self, &[x, y]);
-
-                debug!(
-                    "Skip next instruction if V{:X} ({:#06X}) != V{:X} ({:#06X})",
-                    x, self.v[x as usize], y, self.v[y as usize]
-                );
-            }
-            0xA => {
-                let nnn = rest;
-                OpCodeANNN::execute(self, &[nnn]);
-
-                debug!("Set I to {:#06X} -> {:#06X}", rest, self.i);
+    pub fn execute(&mut self, instruction: Instruction) -> Result<(), Fault> {
+        match instruction {
+            Instruction::ClearScreen => OpCode00E0::execute(self, ()),
+            Instruction::Return => OpCode00EE::execute(self, ()),
+            Instruction::Jump { nnn } => OpCode1NNN::execute(self, nnn),
+            Instruction::Call { nnn } => OpCode2NNN::execute(self, nnn),
+            Instruction::SkipIfEqual { x, nn } => OpCode3XNN::execute(self, (x as usize, nn)),
+            Instruction::SkipIfNotEqual { x, nn } => OpCode4XNN::execute(self, (x as usize, nn)),
+            Instruction::SkipIfRegistersEqual { x, y } => {
+                OpCode5XY0::execute(self, (x as usize, y as usize))
             }
-            0xB => {
-                let nnn = rest;
-                if self.compatibility == Compatibility::Original {
-                    OpCodeBNNN::execute(self, &[nnn]);
-
-                    debug!(
-                        "Jump to {:#06X} + V0 ({:#06X}) -> {:#06X}",
-                        nnn, self.v[0], self.pc
-                    );
-                } else if self.compatibility == Compatibility::New {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeBXNN::execute(self, &[x, nnn]);
-
-                    debug!(
-                        "Jump to {:#06X} + V{:X} ({:#06X}) -> {:#06X}",
-                        nnn, x, self.v[x as usize], self.pc
-                    );
-                }
+            Instruction::Set { x, nn } => OpCode6XNN::execute(self, (x as usize, nn)),
+            Instruction::Add { x, nn } => OpCode7XNN::execute(self, (x as usize, nn)),
+            Instruction::Copy { x, y } => OpCode8XY0::execute(self, (x as usize, y as usize)),
+            Instruction::Or { x, y } => OpCode8XY1::execute(self, (x as usize, y as usize)),
+            Instruction::And { x, y } => OpCode8XY2::execute(self, (x as usize, y as usize)),
+            Instruction::Xor { x, y } => OpCode8XY3::execute(self, (x as usize, y as usize)),
+            Instruction::AddRegisters { x, y } => {
+                OpCode8XY4::execute(self, (x as usize, y as usize))
             }
-            0xC => {
-                let x = (rest & 0xF00) >> 8;
-                let nn = rest & 0x0FF;
-                OpCodeCXNN::execute(self, &[x, nn]);
-
-                debug!(
-                    "Set V{:X} to random byte & {:#06X} -> {:#06X}",
-                    x, nn, self.v[x as usize]
-                );
+            Instruction::Subtract { x, y } => OpCode8XY5::execute(self, (x as usize, y as usize)),
+            Instruction::ShiftRight { x, y } => OpCode8XY6::execute(self, (x as usize, y as usize)),
+            Instruction::SubtractReversed { x, y } => {
+                OpCode8XY7::execute(self, (x as usize, y as usize))
             }
-            0xD => {
-                let x = (rest & 0xF00) >> 8;
-                let y = (rest & 0x0F0) >> 4;
-                let n = rest & 0x00F;
-                OpCodeDXYN::execute(self, &[x, y, n]);
-
-                debug!(
-                    "Draw sprite at {}:{} with height {}",
-                    self.v[x as usize], self.v[y as usize], n
-                );
+            Instruction::ShiftLeft { x, y } => OpCode8XYE::execute(self, (x as usize, y as usize)),
+            Instruction::SkipIfRegistersNotEqual { x, y } => {
+                OpCode9XY0::execute(self, (x as usize, y as usize))
             }
-            0xE => match rest & 0x0FF {
-                0x9E => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeEX9E::execute(self, &[x]);
-
-                    debug!(
-                        "Skip next instruction if key {:#06X} is pressed",
-                        self.v[x as usize]
-                    );
-                }
-                0xA1 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeEXA1::execute(self, &[x]);
-
-                    debug!(
-                        "Skip next instruction if key {:#06X} is not pressed",
-                        self.v[x as usize]
-                    );
-                }
-                _ => {
-                    not_found = true;
-                }
-            },
-            0xF => match rest & 0x0FF {
-                0x07 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX07::execute(self, &[x]);
-
-                    debug!(
-                        "Set V{:X} to delay timer ({:#06X}) -> {:#06X}",
-                        x, self.delay_timer, self.v[x as usize]
-                    );
-                }
-                0x0A => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX0A::execute(self, &[x]);
-
-                    debug!("Wait for keypress and store in V{:X}", x);
-                }
-                0x15 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX15::execute(self, &[x]);
-
-                    debug!(
-                        "Set delay timer to V{:X} ({:#06X}) -> {:#06X}",
-                        x, self.v[x as usize], self.delay_timer
-                    );
-                }
-                0x18 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX18::execute(self, &[x]);
-
-                    debug!(
-                        "Set sound timer to V{:X} ({:#06X}) -> {:#06X}",
-                        x, self.v[x as usize], self.sound_timer
-                    );
-                }
-                0x29 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX29::execute(self, &[x]);
-
-                    debug!(
-                        "Set I to location of sprite for digit V{:X} ({:#06X}) -> {:#06X}",
-                        x, self.v[x as usize], self.i
-                    );
-                }
-                0x33 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX33::execute(self, &[x]);
-
-                    debug!(
-                        "Store BCD representation of V{:X} ({:#06X}) in memory at I ({:#06X})",
-                        x, self.v[x as usize], self.i
-                    );
-                }
-                0x1E => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX1E::execute(self, &[x]);
-
-                    debug!(
-                        "Set I to I + V{:X} ({:#06X}) -> {:#06X}",
-                        x, self.v[x as usize], self.i
-                    );
-                }
-                0x55 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX55::execute(self, &[x]);
-
-                    debug!(
-						"Store registers V0 through V{:X} in memory starting at location I ({:#06X})",
-						x, self.i
-					);
-                }
-                0x65 => {
-                    let x = (rest & 0xF00) >> 8;
-                    OpCodeFX65::execute(self, &[x]);
-
-                    debug!(
-						"Read registers V0 through V{:X} from memory starting at location I ({:#06X})",
-						x, self.i
-					);
-                }
-                _ => {
-                    not_found = true;
-                }
+            Instruction::SetIndex { nnn } => OpCodeANNN::execute(self, nnn),
+            Instruction::JumpWithOffset { x, nnn } => match self.compatibility {
+                Compatibility::Original => OpCodeBNNN::execute(self, nnn),
+                Compatibility::New => OpCodeBXNN::execute(self, (x as usize, nnn)),
             },
-
-            _ => {
-                not_found = true;
-            }
-        }
-
-        if not_found {
-            Err(format!("Opcode {:#06X} not recognized!", first << 0xC | rest).into())
-        } else {
-            Ok(())
+            Instruction::Random { x, nn } => OpCodeCXNN::execute(self, (x as usize, nn)),
+            Instruction::Draw { x, y, n } => OpCodeDXYN::execute(self, (x as usize, y as usize, n)),
+            Instruction::SkipIfKeyPressed { x } => OpCodeEX9E::execute(self, x as usize),
+            Instruction::SkipIfKeyNotPressed { x } => OpCodeEXA1::execute(self, x as usize),
+            Instruction::GetDelayTimer { x } => OpCodeFX07::execute(self, x as usize),
+            Instruction::WaitForKey { x } => OpCodeFX0A::execute(self, x as usize),
+            Instruction::SetDelayTimer { x } => OpCodeFX15::execute(self, x as usize),
+            Instruction::SetSoundTimer { x } => OpCodeFX18::execute(self, x as usize),
+            Instruction::AddToIndex { x } => OpCodeFX1E::execute(self, x as usize),
+            Instruction::FontCharacter { x } => OpCodeFX29::execute(self, x as usize),
+            Instruction::StoreBcd { x } => OpCodeFX33::execute(self, x as usize),
+            Instruction::StoreRegisters { x } => OpCodeFX55::execute(self, x as usize),
+            Instruction::LoadRegisters { x } => OpCodeFX65::execute(self, x as usize),
+            Instruction::Unknown(opcode) => Err(Fault::UnknownOpcode(opcode)),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Processor;
+    use super::{Compatibility, ParseCompatibilityError, Processor};
+    use crate::components::memory::Memory;
+    use crate::fault::Fault;
+    use crate::instruction::decode;
     use array_init::array_init;
     use wasm_bindgen_test::wasm_bindgen_test;
 
+    #[wasm_bindgen_test]
+    fn test_compatibility_from_str() {
+        // Arrange
+        let names = ["original", "new", "schip"];
+
+        // Act
+        let results = names.map(str::parse::<Compatibility>);
+
+        // Assert
+        assert_eq!(
+            results,
+            [
+                Ok(Compatibility::Original),
+                Ok(Compatibility::New),
+                Err(ParseCompatibilityError("schip".to_string()))
+            ]
+        );
+    }
+
     #[wasm_bindgen_test]
     fn test_fetch() {
         // Arrange
@@ -506,7 +407,7 @@ mod tests {
         let pc: u16 = 0x0;
 
         let mut processor = Processor::init();
-        processor.memory.data = memory;
+        processor.memory.load_rom(&memory, 0x0).unwrap();
         processor.pc = pc;
 
         // Act
@@ -518,43 +419,222 @@ mod tests {
     }
 
     #[wasm_bindgen_test]
-    fn test_decode() {
+    fn test_execute_normal() {
+        // Arrange
+        let instruction = decode(0x00E0);
+        let mut processor = Processor::init();
+
+        // Act
+        let result = processor.execute(instruction);
+
+        // Assert
+        assert!(result.is_ok());
+    }
+    #[wasm_bindgen_test]
+    fn test_execute_not_implemented() {
+        // Arrange
+        let instruction = decode(0xFFFF);
+        let mut processor = Processor::init();
+
+        // Act
+        let result = processor.execute(instruction);
+
+        // Assert
+        assert_eq!(result, Err(Fault::UnknownOpcode(0xFFFF)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_reset() {
         // Arrange
-        let instruction: u16 = 0xABCD;
-        let processor = Processor::init();
+        let mut processor = Processor::init();
+        processor.pc = 0x345;
+        processor.i = 0x123;
+        processor.v[0x3] = 0x45;
+        processor.stack.push(0x202).unwrap();
+        processor.delay_timer = 0x10;
+        processor.sound_timer = 0x20;
+        processor.gfx.set(0, 0, true);
+        processor.fault = Some(Fault::StackUnderflow);
+        processor.memory.write(0x300, 0xAB);
 
         // Act
-        let result = processor.decode(instruction);
+        processor.reset(0x600);
 
         // Assert
-        let expected: (u16, u16) = (0xA, 0xBCD);
-        assert_eq!(result, expected, "{:#06X?} =/= {:#06X?}", result, expected);
+        assert_eq!(processor.pc, 0x600);
+        assert_eq!(processor.i, 0x0);
+        assert_eq!(processor.v, [0; 16]);
+        assert!(processor.stack.is_empty());
+        assert_eq!(processor.delay_timer, 0x0);
+        assert_eq!(processor.sound_timer, 0x0);
+        assert!(!processor.gfx.get(0, 0));
+        assert_eq!(processor.fault, None);
+        assert_eq!(
+            processor.memory.data()[0x300],
+            0xAB,
+            "Memory should be kept!"
+        );
     }
 
     #[wasm_bindgen_test]
-    fn test_execute_normal() {
+    fn test_cycle_fatal_fault() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: call 0x200, recursing until the stack runs out
+        processor.memory.write(0x200, 0x22);
+        processor.memory.write(0x201, 0x00);
+
+        // Act
+        for _ in 0..=processor.stack.depth() {
+            processor.cycle();
+        }
+        processor.cycle();
+
+        // Assert
+        assert_eq!(
+            processor.fault,
+            Some(Fault::StackOverflow {
+                depth: processor.stack.depth()
+            })
+        );
+        assert_eq!(
+            processor.pc,
+            Memory::ROM_BEGIN_INDEX,
+            "PC should point at the fault!"
+        );
+        assert_eq!(processor.stack.len(), processor.stack.depth());
+    }
+    #[wasm_bindgen_test]
+    fn test_cycle_unknown_opcode() {
         // Arrange
-        let first = 0x0;
-        let rest = 0x0E0;
         let mut processor = Processor::init();
+        processor.memory.write(0x200, 0xFF);
+        processor.memory.write(0x201, 0xFF);
 
         // Act
-        let result = processor.execute(first, rest);
+        processor.cycle();
 
         // Assert
-        assert!(result.is_ok());
+        assert_eq!(processor.fault, None, "Unknown opcodes should not halt!");
+        assert_eq!(processor.pc, Memory::ROM_BEGIN_INDEX + 2);
     }
     #[wasm_bindgen_test]
-    fn test_execute_not_implemented() {
+    fn test_cycle_pc_past_end() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0xFFE: LD V0, 0x00, the last instruction in memory
+        processor.memory.write(0xFFE, 0x60);
+        processor.pc = 0xFFE;
+
+        // Act
+        processor.cycle();
+
+        // Assert
+        assert_eq!(processor.fault, Some(Fault::PcOutOfBounds(0x1000)));
+        assert_eq!(processor.pc, 0xFFE, "PC should point at the fault!");
+    }
+    #[wasm_bindgen_test]
+    fn test_cycle_unknown_opcode_at_end() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.memory.write(0xFFE, 0xFF);
+        processor.memory.write(0xFFF, 0xFF);
+        processor.pc = 0xFFE;
+
+        // Act
+        processor.cycle();
+
+        // Assert
+        assert_eq!(processor.fault, Some(Fault::PcOutOfBounds(0x1000)));
+    }
+    #[wasm_bindgen_test]
+    fn test_run_jump_with_offset_past_end() {
+        // Arrange
+        let mut processor = Processor::init_compat(Compatibility::Original);
+        // 0x200: JP V0, 0xFFF
+        processor.memory.write(0x200, 0xBF);
+        processor.memory.write(0x201, 0xFF);
+        processor.v[0] = 0xFF;
+
+        // Act
+        let result = processor.run(10);
+
+        // Assert
+        assert_eq!(result, 1);
+        assert_eq!(processor.fault, Some(Fault::PcOutOfBounds(0x10FE)));
+        assert_eq!(processor.pc, 0x200);
+    }
+    #[wasm_bindgen_test]
+    fn test_run_return_past_end() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0xFFE: CALL 0x300, 0x300: RET
+        processor.memory.write(0xFFE, 0x23);
+        processor.memory.write(0x300, 0x00);
+        processor.memory.write(0x301, 0xEE);
+        processor.pc = 0xFFE;
+
+        // Act
+        processor.run(10);
+
+        // Assert
+        assert_eq!(processor.fault, Some(Fault::PcOutOfBounds(0x1000)));
+        assert_eq!(processor.pc, 0x300);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_run() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: V1 += 1, 0x202: jump to 0x200
+        processor
+            .memory
+            .write_bytes(0x200, &[0x71, 0x01, 0x12, 0x00])
+            .unwrap();
+
+        // Act
+        let result = processor.run(10);
+
+        // Assert
+        assert_eq!(result, 10);
+        assert_eq!(processor.v[0x1], 5);
+        assert_eq!(processor.pc, Memory::ROM_BEGIN_INDEX);
+    }
+    #[wasm_bindgen_test]
+    fn test_run_self_modifying_code() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: I = 0x206, 0x202: store V0 at I, 0x204: jump to 0x206, 0x206: overwritten
+        processor
+            .memory
+            .write_bytes(0x200, &[0xA2, 0x06, 0xF0, 0x55, 0x12, 0x06, 0x00, 0x05])
+            .unwrap();
+        processor.v[0x0] = 0x61;
+        processor.memory.instruction(0x206);
+
+        // Act
+        processor.run(4);
+
+        // Assert
+        assert_eq!(
+            processor.v[0x1], 0x05,
+            "Stale instruction executed instead of LD V1, 0x05!"
+        );
+        assert_eq!(processor.fault, None);
+        assert_eq!(processor.pc, 0x208);
+    }
+    #[wasm_bindgen_test]
+    fn test_run_stops_on_fault() {
         // Arrange
-        let first = 0xF;
-        let rest = 0xFFF;
         let mut processor = Processor::init();
+        // 0x200: return without a call
+        processor.memory.write_bytes(0x200, &[0x00, 0xEE]).unwrap();
 
         // Act
-        let result = processor.execute(first, rest);
+        let result = processor.run(10);
 
         // Assert
-        assert!(result.is_err());
+        assert_eq!(result, 1);
+        assert_eq!(processor.fault, Some(Fault::StackUnderflow));
     }
 }
@@ -1,5 +1,6 @@
-use wasm_bindgen::JsCast;
-use web_sys::{window, CanvasRenderingContext2d, HtmlCanvasElement};
+use super::framebuffer::Framebuffer;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{window, CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
 
 #[derive(Debug)]
 pub struct Screen {
@@ -31,25 +32,24 @@ impl Screen {
                     .dyn_into::<CanvasRenderingContext2d>()
                     .expect("Error casting canvas context type!");
 
-                ctx.set_fill_style(&"#000".into());
+                ctx.set_fill_style_str("#000");
                 ctx.fill_rect(0.0, 0.0, Self::WIDTH as f64, Self::HEIGHT as f64);
 
                 ctx
             },
         }
     }
-    pub fn update(&self, gfx: &[u8; Self::HEIGHT * Self::WIDTH]) {
-        for row in 0..Self::HEIGHT {
-            for col in 0..Self::WIDTH {
-                let color = if gfx[row * Self::WIDTH + col] == 1 {
-                    "#fff"
-                } else {
-                    "#000"
-                };
+    pub fn update(&self, framebuffer: &Framebuffer) {
+        let rgba = framebuffer.to_rgba();
+        let image = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&rgba),
+            Self::WIDTH as u32,
+            Self::HEIGHT as u32,
+        )
+        .expect("Error creating image data!");
 
-                self.context.set_fill_style(&color.into());
-                self.context.fill_rect(col as f64, row as f64, 1.0, 1.0);
-            }
-        }
+        self.context
+            .put_image_data(&image, 0.0, 0.0)
+            .expect("Error drawing image data!");
     }
 }
@@ -0,0 +1,101 @@
+use crate::fault::Fault;
+
+#[derive(Debug)]
+pub struct Stack {
+    data: Vec<u16>,
+    depth: usize,
+}
+impl Stack {
+    /** Number of levels on COSMAC VIP successors, SCHIP and XO-CHIP */
+    pub const DEFAULT_DEPTH: usize = 16;
+
+    pub fn init() -> Stack {
+        Stack::with_depth(Stack::DEFAULT_DEPTH)
+    }
+    pub fn with_depth(depth: usize) -> Stack {
+        Stack {
+            data: Vec::with_capacity(depth),
+            depth,
+        }
+    }
+
+    pub fn push(&mut self, address: u16) -> Result<(), Fault> {
+        if self.data.len() >= self.depth {
+            return Err(Fault::StackOverflow { depth: self.depth });
+        }
+
+        self.data.push(address);
+        Ok(())
+    }
+    pub fn pop(&mut self) -> Result<u16, Fault> {
+        self.data.pop().ok_or(Fault::StackUnderflow)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    /** Stack contents, from the bottom (oldest call) to the top */
+    pub fn as_slice(&self) -> &[u16] {
+        &self.data
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stack;
+    use crate::fault::Fault;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_push_pop() {
+        // Arrange
+        let mut stack = Stack::init();
+
+        // Act
+        stack.push(0x200).unwrap();
+        stack.push(0x300).unwrap();
+
+        // Assert
+        assert_eq!(stack.as_slice(), [0x200, 0x300]);
+        assert_eq!(stack.pop(), Ok(0x300));
+        assert_eq!(stack.pop(), Ok(0x200));
+        assert!(stack.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_overflow() {
+        // Arrange
+        let mut stack = Stack::with_depth(2);
+        stack.push(0x200).unwrap();
+        stack.push(0x202).unwrap();
+
+        // Act
+        let result = stack.push(0x204);
+
+        // Assert
+        assert_eq!(result, Err(Fault::StackOverflow { depth: 2 }));
+        assert_eq!(stack.len(), 2, "Stack should not grow past its depth!");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_underflow() {
+        // Arrange
+        let mut stack = Stack::init();
+
+        // Act
+        let result = stack.pop();
+
+        // Assert
+        assert_eq!(result, Err(Fault::StackUnderflow));
+    }
+}
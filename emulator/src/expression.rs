@@ -0,0 +1,414 @@
+use crate::components::processor::Processor;
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExpressionError {
+    /** Byte offset in the expression where parsing failed */
+    pub position: usize,
+    pub message: String,
+}
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+impl std::error::Error for ExpressionError {}
+impl From<ExpressionError> for JsValue {
+    fn from(err: ExpressionError) -> JsValue {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/** Machine state an expression can refer to by name */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    V(u8),
+    I,
+    Pc,
+    DelayTimer,
+    SoundTimer,
+    /** Number of return addresses on the stack */
+    StackPointer,
+    Cycles,
+    /** Times the breakpoint was reached, including this time */
+    HitCount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    BitOr,
+    BitXor,
+    BitAnd,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Not,
+    Negate,
+}
+
+/**
+ * Parsed expression over machine state, e.g. `v3 == 0x10 && i > 0x300`, `[i+2] != 0` or `hit_count > 5`.
+ * Works like C on 64-bit integers, comparisons give 1 or 0 and `[address]` reads a byte of memory.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    Number(i64),
+    Variable(Variable),
+    /** Byte of memory at the address */
+    Memory(Box<Expression>),
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+}
+impl Expression {
+    pub fn parse(text: &str) -> Result<Expression, ExpressionError> {
+        let mut parser = Parser {
+            text,
+            position: 0,
+            depth: 0,
+        };
+        let expression = parser.expression(0)?;
+
+        parser.skip_whitespace();
+        if parser.position < text.len() {
+            return Err(parser.error("Unexpected character"));
+        }
+        Ok(expression)
+    }
+
+    pub fn evaluate(&self, processor: &Processor, hit_count: u64) -> i64 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Variable(variable) => match *variable {
+                Variable::V(x) => processor.v[x as usize] as i64,
+                Variable::I => processor.i as i64,
+                Variable::Pc => processor.pc as i64,
+                Variable::DelayTimer => processor.delay_timer as i64,
+                Variable::SoundTimer => processor.sound_timer as i64,
+                Variable::StackPointer => processor.stack.len() as i64,
+                Variable::Cycles => processor.cycles as i64,
+                Variable::HitCount => hit_count as i64,
+            },
+            Expression::Memory(address) => {
+                let address = address.evaluate(processor, hit_count) as usize;
+                processor.memory.data()[address % processor.memory.data().len()] as i64
+            }
+            Expression::Unary(operator, operand) => {
+                let value = operand.evaluate(processor, hit_count);
+                match operator {
+                    UnaryOperator::Not => (value == 0) as i64,
+                    UnaryOperator::Negate => value.wrapping_neg(),
+                }
+            }
+            Expression::Binary(operator, left, right) => {
+                let left = left.evaluate(processor, hit_count);
+                // Short-circuit like C, so `[i] && ...` doesn't evaluate the rest needlessly
+                match operator {
+                    BinaryOperator::Or if left != 0 => return 1,
+                    BinaryOperator::And if left == 0 => return 0,
+                    _ => {}
+                }
+                let right = right.evaluate(processor, hit_count);
+
+                match operator {
+                    BinaryOperator::Or | BinaryOperator::And => (right != 0) as i64,
+                    BinaryOperator::Equal => (left == right) as i64,
+                    BinaryOperator::NotEqual => (left != right) as i64,
+                    BinaryOperator::Less => (left < right) as i64,
+                    BinaryOperator::LessOrEqual => (left <= right) as i64,
+                    BinaryOperator::Greater => (left > right) as i64,
+                    BinaryOperator::GreaterOrEqual => (left >= right) as i64,
+                    BinaryOperator::BitOr => left | right,
+                    BinaryOperator::BitXor => left ^ right,
+                    BinaryOperator::BitAnd => left & right,
+                    BinaryOperator::ShiftLeft => left.wrapping_shl(right as u32),
+                    BinaryOperator::ShiftRight => left.wrapping_shr(right as u32),
+                    BinaryOperator::Add => left.wrapping_add(right),
+                    BinaryOperator::Subtract => left.wrapping_sub(right),
+                    BinaryOperator::Multiply => left.wrapping_mul(right),
+                }
+            }
+        }
+    }
+}
+
+/** Operators from the loosest binding to the tightest, each level binds tighter than the previous one */
+const PRECEDENCE: &[&[(&str, BinaryOperator)]] = &[
+    &[("||", BinaryOperator::Or)],
+    &[("&&", BinaryOperator::And)],
+    &[("|", BinaryOperator::BitOr)],
+    &[("^", BinaryOperator::BitXor)],
+    &[("&", BinaryOperator::BitAnd)],
+    &[
+        ("==", BinaryOperator::Equal),
+        ("!=", BinaryOperator::NotEqual),
+    ],
+    &[
+        ("<=", BinaryOperator::LessOrEqual),
+        (">=", BinaryOperator::GreaterOrEqual),
+        ("<", BinaryOperator::Less),
+        (">", BinaryOperator::Greater),
+    ],
+    &[
+        ("<<", BinaryOperator::ShiftLeft),
+        (">>", BinaryOperator::ShiftRight),
+    ],
+    &[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)],
+    &[("*", BinaryOperator::Multiply)],
+];
+
+/** Deepest nesting of brackets, unary operators and chained binary operators, deeper ones could overflow the stack */
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    /** Nesting of the expression being parsed */
+    depth: usize,
+}
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ExpressionError {
+        ExpressionError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+    fn rest(&mut self) -> &str {
+        self.skip_whitespace();
+        &self.text[self.position..]
+    }
+    fn nest(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error("Expression is nested too deeply"));
+        }
+        Ok(())
+    }
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            return true;
+        }
+        false
+    }
+
+    /** Binary operators of `level` and tighter binding ones, left associative */
+    fn expression(&mut self, level: usize) -> Result<Expression, ExpressionError> {
+        let Some(operators) = PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+
+        let depth = self.depth;
+        let mut left = self.expression(level + 1)?;
+        'operators: loop {
+            let rest = self.rest();
+            for (token, operator) in operators.iter() {
+                // `|`, `&`, `<` and `>` shouldn't swallow the first half of `||`, `&&`, `<<` and `>>`
+                let Some(after) = rest.strip_prefix(token) else {
+                    continue;
+                };
+                if token.len() == 1 && after.starts_with(token) {
+                    continue;
+                }
+                self.position += token.len();
+                // Each operator nests the expression so far one level deeper
+                self.nest()?;
+                let right = self.expression(level + 1)?;
+                left = Expression::Binary(*operator, Box::new(left), Box::new(right));
+                continue 'operators;
+            }
+            self.depth = depth;
+            return Ok(left);
+        }
+    }
+    fn unary(&mut self) -> Result<Expression, ExpressionError> {
+        if self.eat("!") {
+            self.nest()?;
+            let operand = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expression::Unary(UnaryOperator::Not, Box::new(operand)));
+        }
+        if self.eat("-") {
+            self.nest()?;
+            let operand = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expression::Unary(UnaryOperator::Negate, Box::new(operand)));
+        }
+        self.primary()
+    }
+    fn primary(&mut self) -> Result<Expression, ExpressionError> {
+        if self.eat("(") {
+            self.nest()?;
+            let expression = self.expression(0)?;
+            self.depth -= 1;
+            if !self.eat(")") {
+                return Err(self.error("Expected `)`"));
+            }
+            return Ok(expression);
+        }
+        if self.eat("[") {
+            self.nest()?;
+            let address = self.expression(0)?;
+            self.depth -= 1;
+            if !self.eat("]") {
+                return Err(self.error("Expected `]`"));
+            }
+            return Ok(Expression::Memory(Box::new(address)));
+        }
+
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("Expected a number, name, `(` or `[`"));
+        }
+        let word = rest[..length].to_ascii_lowercase();
+
+        let expression = if word.starts_with(|c: char| c.is_ascii_digit()) {
+            let value = match word.strip_prefix("0x") {
+                Some(hex) => i64::from_str_radix(hex, 16),
+                None => word.parse::<i64>(),
+            };
+            Expression::Number(value.map_err(|_| self.error("Invalid number"))?)
+        } else {
+            let variable = match word.as_str() {
+                "i" => Variable::I,
+                "pc" => Variable::Pc,
+                "dt" => Variable::DelayTimer,
+                "st" => Variable::SoundTimer,
+                "sp" => Variable::StackPointer,
+                "cycles" => Variable::Cycles,
+                "hit_count" => Variable::HitCount,
+                _ => match word.strip_prefix('v').map(|x| u8::from_str_radix(x, 16)) {
+                    Some(Ok(x)) if word.len() == 2 => Variable::V(x),
+                    _ => return Err(self.error(&format!("Unknown name `{}`", word))),
+                },
+            };
+            Expression::Variable(variable)
+        };
+
+        self.position += length;
+        Ok(expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinaryOperator, Expression, Variable};
+    use crate::components::processor::Processor;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn evaluate(text: &str, processor: &Processor) -> i64 {
+        Expression::parse(text).unwrap().evaluate(processor, 6)
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse() {
+        // Arrange
+        let text = "v3 == 0x10 && i > 0x300";
+
+        // Act
+        let result = Expression::parse(text);
+
+        // Assert
+        let register = |variable| Box::new(Expression::Variable(variable));
+        let number = |value| Box::new(Expression::Number(value));
+        assert_eq!(
+            result,
+            Ok(Expression::Binary(
+                BinaryOperator::And,
+                Box::new(Expression::Binary(
+                    BinaryOperator::Equal,
+                    register(Variable::V(3)),
+                    number(0x10)
+                )),
+                Box::new(Expression::Binary(
+                    BinaryOperator::Greater,
+                    register(Variable::I),
+                    number(0x300)
+                )),
+            ))
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_parse_error() {
+        // Arrange
+        let texts = ["v3 ==", "vg == 1", "(v3", "[i", "v3 = 1", "0xZZ"];
+
+        // Act
+        let results = texts.map(Expression::parse);
+
+        // Assert
+        for result in results {
+            assert!(result.is_err());
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_too_deep() {
+        // Arrange
+        let texts = [
+            "(".repeat(10_000) + "1" + &")".repeat(10_000),
+            "-".repeat(10_000) + "1",
+            "!".repeat(10_000) + "1",
+            "[".repeat(10_000) + "1" + &"]".repeat(10_000),
+            "1".to_string() + &"+1".repeat(10_000),
+        ];
+
+        // Act
+        let results = texts.map(|text| Expression::parse(&text));
+
+        // Assert
+        for result in results {
+            assert_eq!(
+                result.unwrap_err().message,
+                "Expression is nested too deeply"
+            );
+        }
+        assert!(Expression::parse(&("(".repeat(32) + "-1" + &")".repeat(32))).is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_evaluate() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.v[0x3] = 0x10;
+        processor.v[0xF] = 0x1;
+        processor.i = 0x301;
+        processor.memory.write(0x303, 0x7);
+
+        // Act
+        let results = [
+            "v3 == 0x10 && i > 0x300",
+            "[i+2] != 0",
+            "[i + 2] * 2",
+            "hit_count > 5",
+            "vF | v3 << 1 & 0xFF",
+            "!(v3 <= 16) || -1 + 1",
+            "1 - 2 - 3",
+        ]
+        .map(|text| evaluate(text, &processor));
+
+        // Assert
+        assert_eq!(results, [1, 1, 14, 1, 0x21, 0, -4]);
+    }
+}
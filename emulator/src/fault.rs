@@ -0,0 +1,46 @@
+use std::fmt;
+
+/** Errors raised by the emulated program, as opposed to errors of the emulator itself */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /** Subroutine call with every stack level already in use */
+    StackOverflow { depth: usize },
+
+    /** Return from subroutine with an empty stack */
+    StackUnderflow,
+
+    /** Instruction which doesn't match any known opcode */
+    UnknownOpcode(u16),
+
+    /** Jump or skip past the last instruction in memory, with the address it went to */
+    PcOutOfBounds(u16),
+
+    /** Instruction using I to read or write past the end of memory, with the first address outside it */
+    MemoryOutOfBounds { address: usize },
+}
+impl Fault {
+    /** Fatal faults halt the processor, the rest are only logged */
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, Fault::UnknownOpcode(_))
+    }
+}
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::StackOverflow { depth } => {
+                write!(f, "Stack overflow (more than {} nested calls)!", depth)
+            }
+            Fault::StackUnderflow => write!(f, "Stack underflow (return without a call)!"),
+            Fault::UnknownOpcode(opcode) => write!(f, "Opcode {:#06X} not recognized!", opcode),
+            Fault::PcOutOfBounds(pc) => write!(f, "PC {:#06X} is past the end of memory!", pc),
+            Fault::MemoryOutOfBounds { address } => {
+                write!(
+                    f,
+                    "Memory access at {:#06X} is past the end of memory!",
+                    address
+                )
+            }
+        }
+    }
+}
+impl std::error::Error for Fault {}
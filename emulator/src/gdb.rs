@@ -0,0 +1,621 @@
+use crate::breakpoints::Breakpoint;
+use crate::components::memory::Memory;
+use crate::components::processor::Processor;
+use crate::fault::Fault;
+use crate::Emulator;
+use log::*;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+/** Byte stream to a GDB client */
+pub trait Connection: Read + Write {
+    /** Whether the client asked to interrupt the running program since the last check, mustn't block */
+    fn poll_interrupt(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+impl Connection for TcpStream {
+    fn poll_interrupt(&mut self) -> io::Result<bool> {
+        self.set_nonblocking(true)?;
+        let mut byte = [0];
+        let result = self.peek(&mut byte);
+        self.set_nonblocking(false)?;
+
+        match result {
+            // The client is gone, stop so the next read ends the session
+            Ok(0) => Ok(true),
+            Ok(_) if byte[0] == INTERRUPT => self.read_exact(&mut byte).map(|_| true),
+            Ok(_) => Ok(false),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<C: Connection + ?Sized> Connection for &mut C {
+    fn poll_interrupt(&mut self) -> io::Result<bool> {
+        (**self).poll_interrupt()
+    }
+}
+
+/** Sent by the client outside of packets to stop a running program, like Ctrl-C */
+const INTERRUPT: u8 = 0x03;
+/** Cycles run between checks for breakpoints, faults and interrupts while continuing */
+const CONTINUE_CHUNK: u32 = 10_000;
+
+/** Register numbers used by `p`, `P` and the target description: V0-VF, then I, PC, DT and ST */
+const REGISTER_I: usize = 16;
+const REGISTER_PC: usize = 17;
+const REGISTER_DT: usize = 18;
+const REGISTER_ST: usize = 19;
+const REGISTER_COUNT: usize = 20;
+
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+const SIGSEGV: u8 = 11;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.chip8.core">
+    <reg name="v0" bitsize="8" type="uint8"/>
+    <reg name="v1" bitsize="8" type="uint8"/>
+    <reg name="v2" bitsize="8" type="uint8"/>
+    <reg name="v3" bitsize="8" type="uint8"/>
+    <reg name="v4" bitsize="8" type="uint8"/>
+    <reg name="v5" bitsize="8" type="uint8"/>
+    <reg name="v6" bitsize="8" type="uint8"/>
+    <reg name="v7" bitsize="8" type="uint8"/>
+    <reg name="v8" bitsize="8" type="uint8"/>
+    <reg name="v9" bitsize="8" type="uint8"/>
+    <reg name="va" bitsize="8" type="uint8"/>
+    <reg name="vb" bitsize="8" type="uint8"/>
+    <reg name="vc" bitsize="8" type="uint8"/>
+    <reg name="vd" bitsize="8" type="uint8"/>
+    <reg name="ve" bitsize="8" type="uint8"/>
+    <reg name="vf" bitsize="8" type="uint8"/>
+    <reg name="i" bitsize="16" type="data_ptr"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+    <reg name="dt" bitsize="8" type="uint8"/>
+    <reg name="st" bitsize="8" type="uint8"/>
+  </feature>
+</target>
+"#;
+
+/**
+ * Serves one GDB remote serial protocol session until the client detaches, kills or disconnects.
+ * Registers are V0-VF, I, PC, DT and ST in that order, multi-byte ones little endian,
+ * and the address space is the 4 KiB of memory.
+ */
+pub fn serve(emulator: &mut Emulator, connection: impl Connection) -> io::Result<()> {
+    let mut stub = GdbStub {
+        processor: &mut emulator.processor,
+        connection,
+        acks: true,
+    };
+    stub.run()
+}
+
+/** What the session does after replying to a packet */
+enum Reply {
+    Packet(String),
+    /** Sends the packet, then ends the session */
+    Last(String),
+    /** `k` doesn't get a reply */
+    Kill,
+}
+
+struct GdbStub<'a, C: Connection> {
+    processor: &'a mut Processor,
+    connection: C,
+    /** Cleared once the client switches to no-ack mode */
+    acks: bool,
+}
+impl<C: Connection> GdbStub<'_, C> {
+    fn run(&mut self) -> io::Result<()> {
+        loop {
+            let Some(packet) = self.read_packet()? else {
+                return Ok(());
+            };
+            debug!("gdb <- {}", packet);
+
+            match self.handle(&packet) {
+                Reply::Packet(reply) => self.write_packet(&reply)?,
+                Reply::Last(reply) => return self.write_packet(&reply),
+                Reply::Kill => return Ok(()),
+            }
+        }
+    }
+
+    /** Next packet's data with escapes resolved, `None` when the client disconnects */
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'$') => {}
+                // Acks, and interrupts while the program isn't running anyway
+                Some(_) => continue,
+            }
+
+            let mut data = Vec::new();
+            let mut checksum: u8 = 0;
+            loop {
+                let Some(byte) = self.read_byte()? else {
+                    return Ok(None);
+                };
+                match byte {
+                    b'#' => break,
+                    b'}' => {
+                        let Some(escaped) = self.read_byte()? else {
+                            return Ok(None);
+                        };
+                        checksum = checksum.wrapping_add(byte).wrapping_add(escaped);
+                        data.push(escaped ^ 0x20);
+                    }
+                    _ => {
+                        checksum = checksum.wrapping_add(byte);
+                        data.push(byte);
+                    }
+                }
+            }
+
+            let (Some(high), Some(low)) = (self.read_byte()?, self.read_byte()?) else {
+                return Ok(None);
+            };
+            let expected = [high, low];
+            let valid = std::str::from_utf8(&expected)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                == Some(checksum);
+
+            if self.acks {
+                self.connection.write_all(if valid { b"+" } else { b"-" })?;
+            }
+            if valid {
+                return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+            }
+            warn!("Dropped GDB packet with a bad checksum");
+        }
+    }
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.connection.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        debug!("gdb -> {}", data);
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.connection, "${}#{:02x}", data, checksum)?;
+        self.connection.flush()
+    }
+
+    fn handle(&mut self, packet: &str) -> Reply {
+        let arguments = packet.get(1..).unwrap_or_default();
+        let reply = match (packet.chars().next(), arguments) {
+            (Some('?'), _) => self.stop_reply(),
+            (Some('g'), _) => self.read_registers(),
+            (Some('G'), hex) => ok_or_error(self.write_registers(hex)),
+            (Some('p'), number) => usize::from_str_radix(number, 16)
+                .ok()
+                .and_then(|number| self.read_register(number))
+                .unwrap_or_else(|| error(1)),
+            (Some('P'), assignment) => ok_or_error(self.write_register(assignment)),
+            (Some('m'), range) => self.read_memory(range).unwrap_or_else(|| error(1)),
+            (Some('M'), write) => ok_or_error(self.write_memory(write)),
+            (Some('c'), address) => match self.jump(address) {
+                Some(()) => self.resume(),
+                None => error(1),
+            },
+            (Some('s'), address) => match self.jump(address) {
+                Some(()) => {
+                    self.processor.resume();
+                    self.processor.cycle();
+                    self.stop_reply()
+                }
+                None => error(1),
+            },
+            (Some('Z'), breakpoint) => self.set_breakpoint(breakpoint, true),
+            (Some('z'), breakpoint) => self.set_breakpoint(breakpoint, false),
+            (Some('H'), _) => "OK".to_string(),
+            (Some('D'), _) => return Reply::Last("OK".to_string()),
+            (Some('k'), _) => return Reply::Kill,
+            _ => self.query(packet),
+        };
+
+        Reply::Packet(reply)
+    }
+    fn query(&mut self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            return "PacketSize=4000;qXfer:features:read+;QStartNoAckMode+".to_string();
+        }
+        if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            return read_chunk(TARGET_XML, range).unwrap_or_else(|| error(1));
+        }
+
+        match packet {
+            "QStartNoAckMode" => {
+                self.acks = false;
+                "OK".to_string()
+            }
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            // Empty means unsupported, the client falls back to packets we do support
+            _ => String::new(),
+        }
+    }
+
+    /** Why the program is stopped */
+    fn stop_reply(&self) -> String {
+        let signal = match self.processor.fault {
+            Some(
+                Fault::StackOverflow { .. }
+                | Fault::StackUnderflow
+                | Fault::PcOutOfBounds(_)
+                | Fault::MemoryOutOfBounds { .. },
+            ) => SIGSEGV,
+            Some(Fault::UnknownOpcode(_)) => SIGILL,
+            None => SIGTRAP,
+        };
+        format!("S{:02x}", signal)
+    }
+    /** Runs until a breakpoint, a fatal fault or an interrupt from the client */
+    fn resume(&mut self) -> String {
+        self.processor.resume();
+        loop {
+            self.processor.run(CONTINUE_CHUNK);
+            if self.processor.hit_breakpoint.is_some() || self.processor.fault.is_some() {
+                return self.stop_reply();
+            }
+            match self.connection.poll_interrupt() {
+                Ok(false) => {}
+                Ok(true) | Err(_) => return format!("S{:02x}", SIGINT),
+            }
+        }
+    }
+    /** `c` and `s` can continue from a different address */
+    fn jump(&mut self, address: &str) -> Option<()> {
+        if !address.is_empty() {
+            let address = u16::from_str_radix(address, 16).ok();
+            self.processor.pc = address.filter(|&address| is_valid_pc(address))?;
+        }
+        Some(())
+    }
+
+    fn read_registers(&self) -> String {
+        (0..REGISTER_COUNT)
+            .filter_map(|number| self.read_register(number))
+            .collect()
+    }
+    fn write_registers(&mut self, hex: &str) -> Option<()> {
+        let bytes = decode_hex(hex)?;
+        let mut bytes = bytes.as_slice();
+        for number in 0..REGISTER_COUNT {
+            let size = register_size(number);
+            if bytes.len() < size {
+                return None;
+            }
+            self.set_register(number, &bytes[..size])?;
+            bytes = &bytes[size..];
+        }
+        Some(())
+    }
+    fn read_register(&self, number: usize) -> Option<String> {
+        let processor = &self.processor;
+        let bytes = match number {
+            0..=15 => vec![processor.v[number]],
+            REGISTER_I => processor.i.to_le_bytes().to_vec(),
+            REGISTER_PC => processor.pc.to_le_bytes().to_vec(),
+            REGISTER_DT => vec![processor.delay_timer],
+            REGISTER_ST => vec![processor.sound_timer],
+            _ => return None,
+        };
+        Some(encode_hex(&bytes))
+    }
+    fn write_register(&mut self, assignment: &str) -> Option<()> {
+        let (number, hex) = assignment.split_once('=')?;
+        let number = usize::from_str_radix(number, 16).ok()?;
+        let bytes = decode_hex(hex)?;
+        if number >= REGISTER_COUNT || bytes.len() != register_size(number) {
+            return None;
+        }
+
+        self.set_register(number, &bytes)
+    }
+    /** `bytes` must be as long as the register, fails on a PC outside memory */
+    fn set_register(&mut self, number: usize, bytes: &[u8]) -> Option<()> {
+        let processor = &mut self.processor;
+        match number {
+            0..=15 => processor.v[number] = bytes[0],
+            REGISTER_I => processor.i = u16::from_le_bytes([bytes[0], bytes[1]]),
+            REGISTER_PC => {
+                let pc = u16::from_le_bytes([bytes[0], bytes[1]]);
+                processor.pc = Some(pc).filter(|&pc| is_valid_pc(pc))?;
+            }
+            REGISTER_DT => processor.delay_timer = bytes[0],
+            REGISTER_ST => processor.sound_timer = bytes[0],
+            _ => {}
+        }
+        Some(())
+    }
+
+    /** Reads running past the end of memory are cut short, reads starting outside it are errors */
+    fn read_memory(&self, range: &str) -> Option<String> {
+        let (address, length) =
+            parse_range(range).filter(|&(address, _)| (address as usize) < Memory::SIZE)?;
+        Some(encode_hex(self.processor.memory.read(address, length)))
+    }
+    fn write_memory(&mut self, write: &str) -> Option<()> {
+        let (range, hex) = write.split_once(':')?;
+        let (address, length) = parse_range(range)?;
+        let bytes = decode_hex(hex).filter(|bytes| bytes.len() == length)?;
+
+        self.processor.memory.write_bytes(address, &bytes).ok()
+    }
+
+    /** Only software breakpoints (`Z0`) are supported, hardware ones are the same thing here anyway */
+    fn set_breakpoint(&mut self, breakpoint: &str, insert: bool) -> String {
+        let mut fields = breakpoint.split(',');
+        let (Some("0" | "1"), Some(address)) = (fields.next(), fields.next()) else {
+            return String::new();
+        };
+        let Some(address) = u16::from_str_radix(address, 16)
+            .ok()
+            .filter(|&address| (address as usize) < Memory::SIZE)
+        else {
+            return error(1);
+        };
+
+        match insert {
+            true => self
+                .processor
+                .breakpoints
+                .insert(address, Breakpoint::default()),
+            false => self.processor.breakpoints.remove(address),
+        }
+        "OK".to_string()
+    }
+}
+
+/** Whether a whole instruction can be fetched at `pc` */
+fn is_valid_pc(pc: u16) -> bool {
+    (pc as usize) < Memory::SIZE - 1
+}
+fn register_size(number: usize) -> usize {
+    match number {
+        REGISTER_I | REGISTER_PC => 2,
+        _ => 1,
+    }
+}
+
+fn ok_or_error(result: Option<()>) -> String {
+    match result {
+        Some(()) => "OK".to_string(),
+        None => error(1),
+    }
+}
+fn error(code: u8) -> String {
+    format!("E{:02x}", code)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+/** `<address>,<length>` in hex */
+fn parse_range(range: &str) -> Option<(u16, usize)> {
+    let (address, length) = range.split_once(',')?;
+    let address = u16::from_str_radix(address, 16).ok()?;
+    let length = usize::from_str_radix(length, 16).ok()?;
+
+    Some((address, length))
+}
+/** `qXfer` reply with part of `document`, `m` if there's more to read and `l` if it's the last part */
+fn read_chunk(document: &str, range: &str) -> Option<String> {
+    let (offset, length) = range.split_once(',')?;
+    let offset = usize::from_str_radix(offset, 16).ok()?.min(document.len());
+    let length = usize::from_str_radix(length, 16).ok()?;
+
+    let end = offset.saturating_add(length).min(document.len());
+    let marker = if end < document.len() { 'm' } else { 'l' };
+    Some(format!("{}{}", marker, &document[offset..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serve, Connection};
+    use crate::{Compatibility, Emulator};
+    use std::io::{self, Cursor, Read, Write};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /** Client which sends every packet up front, then disconnects */
+    struct ScriptedClient {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+    impl Read for ScriptedClient {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+    impl Write for ScriptedClient {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    impl Connection for ScriptedClient {}
+
+    fn packet(data: &str) -> String {
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        format!("${}#{:02x}", data, checksum)
+    }
+    /** Serves the packets in no-ack mode, returns the replies to them */
+    fn session(emulator: &mut Emulator, packets: &[&str]) -> Vec<String> {
+        let mut input = packet("QStartNoAckMode");
+        for data in packets {
+            input += &packet(data);
+        }
+        let mut client = ScriptedClient {
+            input: Cursor::new(input.into_bytes()),
+            output: Vec::new(),
+        };
+
+        serve(emulator, &mut client).unwrap();
+
+        let output = String::from_utf8(client.output).unwrap();
+        output
+            .trim_start_matches('+')
+            .split('$')
+            .skip(2)
+            .map(|reply| reply.split_once('#').unwrap().0.to_string())
+            .collect()
+    }
+    fn emulator_with_rom(rom: &[u8]) -> Emulator {
+        let mut emulator = Emulator::headless(Compatibility::Original);
+        emulator.load_rom(rom.to_vec()).unwrap();
+
+        emulator
+    }
+
+    #[wasm_bindgen_test]
+    fn test_registers() {
+        // Arrange
+        let mut emulator = emulator_with_rom(&[]);
+
+        // Act
+        let replies = session(
+            &mut emulator,
+            &["P3=2a", "P10=a402", "p3", "p10", "p11", "p14", "g"],
+        );
+
+        // Assert
+        assert_eq!(replies[..6], ["OK", "OK", "2a", "a402", "0002", "E01"]);
+        assert_eq!(replies[6], "0000002a000000000000000000000000a40200020000");
+        assert_eq!(emulator.state().i, 0x2A4);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_invalid_pc() {
+        // Arrange
+        let mut emulator = emulator_with_rom(&[]);
+
+        // Act
+        let replies = session(
+            &mut emulator,
+            &["P11=ff0f", "P11=0103", "p11", "c1000", "sfff", "p11"],
+        );
+
+        // Assert
+        assert_eq!(replies, ["E01", "OK", "0103", "E01", "E01", "0103"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_memory() {
+        // Arrange
+        let mut emulator = emulator_with_rom(&[0x12, 0x34]);
+
+        // Act
+        let replies = session(
+            &mut emulator,
+            &[
+                "m200,2",
+                "M300,3:aabbcc",
+                "m300,4",
+                "MFFF,2:0102",
+                "mffe,4",
+                "m1000,2",
+            ],
+        );
+
+        // Assert
+        assert_eq!(replies, ["1234", "OK", "aabbcc00", "E01", "0000", "E01"]);
+    }
+    #[wasm_bindgen_test]
+    fn test_disconnect_mid_packet() {
+        // Arrange
+        let mut emulator = emulator_with_rom(&[]);
+        let mut client = ScriptedClient {
+            input: Cursor::new(b"$g#6".to_vec()),
+            output: Vec::new(),
+        };
+
+        // Act
+        let result = serve(&mut emulator, &mut client);
+
+        // Assert
+        assert!(
+            result.is_ok(),
+            "A client leaving mid-packet should end the session!"
+        );
+        assert!(client.output.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_breakpoint_and_step() {
+        // Arrange
+        // 0x200: V0 += 1, 0x202: V1 += 1, 0x204: jump to 0x200
+        let mut emulator = emulator_with_rom(&[0x70, 0x01, 0x71, 0x01, 0x12, 0x00]);
+
+        // Act
+        let replies = session(
+            &mut emulator,
+            &[
+                "Z0,204,2", "c", "p11", "s", "p11", "c", "p0", "z0,204,2", "D",
+            ],
+        );
+
+        // Assert
+        assert_eq!(
+            replies,
+            ["OK", "S05", "0402", "S05", "0002", "S05", "02", "OK", "OK"]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fault_stop_reply() {
+        // Arrange
+        // 0x200: return without a call
+        let mut emulator = emulator_with_rom(&[0x00, 0xEE]);
+
+        // Act
+        let replies = session(&mut emulator, &["c", "?"]);
+
+        // Assert
+        assert_eq!(replies, ["S0b", "S0b"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_target_description() {
+        // Arrange
+        let mut emulator = emulator_with_rom(&[]);
+
+        // Act
+        let replies = session(
+            &mut emulator,
+            &[
+                "qXfer:features:read:target.xml:0,10",
+                "qXfer:features:read:target.xml:0,fff",
+            ],
+        );
+
+        // Assert
+        assert_eq!(replies[0], "m<?xml version=\"1");
+        assert!(replies[1].starts_with('l'));
+        assert!(replies[1].ends_with("</target>\n"));
+    }
+}
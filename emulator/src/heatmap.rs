@@ -0,0 +1,125 @@
+use crate::components::memory::Memory;
+use crate::instruction::Instruction;
+use crate::profiler::data_accesses;
+
+/**
+ * Recent memory accesses of the running program, rendered as one pixel per byte:
+ * red for writes, green for reads and blue for executes, fading out as the program keeps running
+ */
+#[derive(Debug)]
+pub struct Heatmap {
+    /** Cycles after which an access fades to half brightness */
+    pub half_life: u64,
+    /** Counts recorded cycles, independent of resets */
+    clock: u64,
+    /** Clock of the last access of every address, 0 if it never happened */
+    reads: Vec<u64>,
+    writes: Vec<u64>,
+    executes: Vec<u64>,
+}
+impl Heatmap {
+    pub const WIDTH: usize = 64;
+    pub const HEIGHT: usize = Memory::SIZE / Heatmap::WIDTH;
+
+    pub fn init(half_life: u64) -> Heatmap {
+        Heatmap {
+            half_life: half_life.max(1),
+            clock: 0,
+            reads: vec![0; Memory::SIZE],
+            writes: vec![0; Memory::SIZE],
+            executes: vec![0; Memory::SIZE],
+        }
+    }
+
+    /** Records an instruction about to execute at `pc`, with `i` being the index register before it runs */
+    pub fn record(&mut self, pc: u16, instruction: &Instruction, i: u16) {
+        self.clock += 1;
+
+        let pc = pc as usize;
+        for executes in self.executes[pc..(pc + 2).min(Memory::SIZE)].iter_mut() {
+            *executes = self.clock;
+        }
+        let (reads, writes) = data_accesses(instruction, i);
+        self.reads[reads].fill(self.clock);
+        self.writes[writes].fill(self.clock);
+    }
+
+    /** RGBA pixels, row by row, with address 0 at the top left */
+    pub fn image(&self) -> Vec<u8> {
+        let mut image = Vec::with_capacity(Memory::SIZE * 4);
+
+        for address in 0..Memory::SIZE {
+            image.push(self.brightness(self.writes[address]));
+            image.push(self.brightness(self.reads[address]));
+            image.push(self.brightness(self.executes[address]));
+            image.push(0xFF);
+        }
+
+        image
+    }
+    fn brightness(&self, accessed: u64) -> u8 {
+        if accessed == 0 {
+            return 0;
+        }
+
+        let age = (self.clock - accessed) as f64 / self.half_life as f64;
+        (255.0 * 0.5f64.powf(age)).round() as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Heatmap;
+    use crate::components::processor::Processor;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn pixel(image: &[u8], address: usize) -> &[u8] {
+        &image[address * 4..address * 4 + 4]
+    }
+
+    #[wasm_bindgen_test]
+    fn test_image() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: I = 0x300, 0x202: store BCD of V0 at I, 0x204: draw 1 byte at I
+        processor
+            .memory
+            .write_bytes(0x200, &[0xA3, 0x00, 0xF0, 0x33, 0xD0, 0x11])
+            .unwrap();
+        processor.heatmap = Some(Heatmap::init(1));
+        processor.run(3);
+
+        // Act
+        let image = processor.heatmap.unwrap().image();
+
+        // Assert
+        assert_eq!(image.len(), Heatmap::WIDTH * Heatmap::HEIGHT * 4);
+        assert_eq!(pixel(&image, 0x1FF), [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(pixel(&image, 0x204), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(pixel(&image, 0x300), [0x80, 0xFF, 0x00, 0xFF]);
+        assert_eq!(pixel(&image, 0x302), [0x80, 0x00, 0x00, 0xFF]);
+    }
+    #[wasm_bindgen_test]
+    fn test_image_decay() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: jump to 0x202, 0x202: jump to 0x202
+        processor
+            .memory
+            .write_bytes(0x200, &[0x12, 0x02, 0x12, 0x02])
+            .unwrap();
+        processor.heatmap = Some(Heatmap::init(2));
+
+        // Act
+        processor.run(5);
+
+        // Assert
+        let image = processor.heatmap.unwrap().image();
+        assert_eq!(
+            pixel(&image, 0x200)[2],
+            0x40,
+            "Should fade to a quarter after two half lives!"
+        );
+        assert_eq!(pixel(&image, 0x202)[2], 0xFF);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::components::keypad::Keypad;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    /** Number of cycles executed before the event happens */
+    pub cycle: u64,
+    /** Pressed key, `None` releases the key */
+    pub key: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+impl std::error::Error for ScriptError {}
+
+/**
+ * Key presses and releases at fixed cycles, so runs can be repeated exactly.
+ *
+ * One event per line, `<cycle> <key>` presses a hex key and `<cycle> -` releases it.
+ * Empty lines and lines starting with `#` are skipped.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputScript {
+    /** Sorted by cycle, events on the same cycle keep their order */
+    events: Vec<InputEvent>,
+}
+impl InputScript {
+    pub fn init(mut events: Vec<InputEvent>) -> InputScript {
+        events.sort_by_key(|event| event.cycle);
+        InputScript { events }
+    }
+
+    pub fn parse(text: &str) -> Result<InputScript, ScriptError> {
+        let mut events = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| ScriptError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+
+            let mut parts = line.split_whitespace();
+            let cycle = parts
+                .next()
+                .and_then(|cycle| cycle.parse::<u64>().ok())
+                .ok_or_else(|| error("Expected a cycle number"))?;
+            let key = match parts.next() {
+                Some("-") => None,
+                Some(key) => match u8::from_str_radix(key, 16) {
+                    Ok(key) if key <= 0xF => Some(key),
+                    _ => return Err(error("Expected a key from 0 to F or -")),
+                },
+                None => return Err(error("Expected a key from 0 to F or -")),
+            };
+            if parts.next().is_some() {
+                return Err(error("Unexpected text after the key"));
+            }
+
+            events.push(InputEvent { cycle, key });
+        }
+
+        Ok(InputScript::init(events))
+    }
+
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /** Applies the events which happen after `cycle` cycles */
+    pub fn apply(&self, cycle: u64, keypad: &mut Keypad) {
+        let begin = self.events.partition_point(|event| event.cycle < cycle);
+
+        for event in self.events[begin..]
+            .iter()
+            .take_while(|event| event.cycle == cycle)
+        {
+            match event.key {
+                Some(key) => keypad.set_key(key),
+                None => keypad.unset_key(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputEvent, InputScript};
+    use crate::components::keypad::Keypad;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_parse() {
+        // Arrange
+        let text = "# Start the game\n200 5\n\n240 -\n100 a\n";
+
+        // Act
+        let result = InputScript::parse(text);
+
+        // Assert
+        assert_eq!(
+            result.unwrap().events(),
+            [
+                InputEvent {
+                    cycle: 100,
+                    key: Some(0xA)
+                },
+                InputEvent {
+                    cycle: 200,
+                    key: Some(0x5)
+                },
+                InputEvent {
+                    cycle: 240,
+                    key: None
+                },
+            ]
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_parse_error() {
+        // Arrange
+        let text = "100 5\n200 G\n";
+
+        // Act
+        let result = InputScript::parse(text);
+
+        // Assert
+        let err = result.unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_apply() {
+        // Arrange
+        let script = InputScript::parse("10 5\n20 -\n").unwrap();
+        let mut keypad = Keypad::default();
+
+        // Act
+        script.apply(9, &mut keypad);
+        let before = keypad.is_key_pressed();
+        script.apply(10, &mut keypad);
+        let during = (keypad.is_key_pressed(), keypad.get_current_key());
+        script.apply(20, &mut keypad);
+        let after = keypad.is_key_pressed();
+
+        // Assert
+        assert!(!before);
+        assert_eq!(during, (true, 0x5));
+        assert!(!after);
+    }
+}
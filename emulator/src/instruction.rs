@@ -0,0 +1,361 @@
+use crate::components::processor::Compatibility;
+use std::fmt;
+
+/** Decoded instruction with its operands, `x` and `y` are register indices */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /** 00E0 */
+    ClearScreen,
+    /** 00EE */
+    Return,
+    /** 1NNN */
+    Jump { nnn: u16 },
+    /** 2NNN */
+    Call { nnn: u16 },
+    /** 3XNN */
+    SkipIfEqual { x: u8, nn: u8 },
+    /** 4XNN */
+    SkipIfNotEqual { x: u8, nn: u8 },
+    /** 5XY0 */
+    SkipIfRegistersEqual { x: u8, y: u8 },
+    /** 6XNN */
+    Set { x: u8, nn: u8 },
+    /** 7XNN */
+    Add { x: u8, nn: u8 },
+    /** 8XY0 */
+    Copy { x: u8, y: u8 },
+    /** 8XY1 */
+    Or { x: u8, y: u8 },
+    /** 8XY2 */
+    And { x: u8, y: u8 },
+    /** 8XY3 */
+    Xor { x: u8, y: u8 },
+    /** 8XY4 */
+    AddRegisters { x: u8, y: u8 },
+    /** 8XY5 */
+    Subtract { x: u8, y: u8 },
+    /** 8XY6 */
+    ShiftRight { x: u8, y: u8 },
+    /** 8XY7 */
+    SubtractReversed { x: u8, y: u8 },
+    /** 8XYE */
+    ShiftLeft { x: u8, y: u8 },
+    /** 9XY0 */
+    SkipIfRegistersNotEqual { x: u8, y: u8 },
+    /** ANNN */
+    SetIndex { nnn: u16 },
+    /** BNNN on original systems, BXNN on newer ones */
+    JumpWithOffset { x: u8, nnn: u16 },
+    /** CXNN */
+    Random { x: u8, nn: u8 },
+    /** DXYN */
+    Draw { x: u8, y: u8, n: u8 },
+    /** EX9E */
+    SkipIfKeyPressed { x: u8 },
+    /** EXA1 */
+    SkipIfKeyNotPressed { x: u8 },
+    /** FX07 */
+    GetDelayTimer { x: u8 },
+    /** FX0A */
+    WaitForKey { x: u8 },
+    /** FX15 */
+    SetDelayTimer { x: u8 },
+    /** FX18 */
+    SetSoundTimer { x: u8 },
+    /** FX1E */
+    AddToIndex { x: u8 },
+    /** FX29 */
+    FontCharacter { x: u8 },
+    /** FX33 */
+    StoreBcd { x: u8 },
+    /** FX55 */
+    StoreRegisters { x: u8 },
+    /** FX65 */
+    LoadRegisters { x: u8 },
+    /** Anything else, keeps the raw opcode */
+    Unknown(u16),
+}
+
+pub fn decode(opcode: u16) -> Instruction {
+    let x = ((opcode & 0x0F00) >> 8) as u8;
+    let y = ((opcode & 0x00F0) >> 4) as u8;
+    let n = (opcode & 0x000F) as u8;
+    let nn = (opcode & 0x00FF) as u8;
+    let nnn = opcode & 0x0FFF;
+
+    match (opcode & 0xF000) >> 0xC {
+        0x0 => match nnn {
+            0x0E0 => Instruction::ClearScreen,
+            0x0EE => Instruction::Return,
+            _ => Instruction::Unknown(opcode),
+        },
+        0x1 => Instruction::Jump { nnn },
+        0x2 => Instruction::Call { nnn },
+        0x3 => Instruction::SkipIfEqual { x, nn },
+        0x4 => Instruction::SkipIfNotEqual { x, nn },
+        0x5 if n == 0x0 => Instruction::SkipIfRegistersEqual { x, y },
+        0x6 => Instruction::Set { x, nn },
+        0x7 => Instruction::Add { x, nn },
+        0x8 => match n {
+            0x0 => Instruction::Copy { x, y },
+            0x1 => Instruction::Or { x, y },
+            0x2 => Instruction::And { x, y },
+            0x3 => Instruction::Xor { x, y },
+            0x4 => Instruction::AddRegisters { x, y },
+            0x5 => Instruction::Subtract { x, y },
+            0x6 => Instruction::ShiftRight { x, y },
+            0x7 => Instruction::SubtractReversed { x, y },
+            0xE => Instruction::ShiftLeft { x, y },
+            _ => Instruction::Unknown(opcode),
+        },
+        0x9 if n == 0x0 => Instruction::SkipIfRegistersNotEqual { x, y },
+        0xA => Instruction::SetIndex { nnn },
+        0xB => Instruction::JumpWithOffset { x, nnn },
+        0xC => Instruction::Random { x, nn },
+        0xD => Instruction::Draw { x, y, n },
+        0xE => match nn {
+            0x9E => Instruction::SkipIfKeyPressed { x },
+            0xA1 => Instruction::SkipIfKeyNotPressed { x },
+            _ => Instruction::Unknown(opcode),
+        },
+        0xF => match nn {
+            0x07 => Instruction::GetDelayTimer { x },
+            0x0A => Instruction::WaitForKey { x },
+            0x15 => Instruction::SetDelayTimer { x },
+            0x18 => Instruction::SetSoundTimer { x },
+            0x1E => Instruction::AddToIndex { x },
+            0x29 => Instruction::FontCharacter { x },
+            0x33 => Instruction::StoreBcd { x },
+            0x55 => Instruction::StoreRegisters { x },
+            0x65 => Instruction::LoadRegisters { x },
+            _ => Instruction::Unknown(opcode),
+        },
+        _ => Instruction::Unknown(opcode),
+    }
+}
+
+impl Instruction {
+    /** Opcode pattern the instruction was decoded from, e.g. `DXYN` */
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            Instruction::ClearScreen => "00E0",
+            Instruction::Return => "00EE",
+            Instruction::Jump { .. } => "1NNN",
+            Instruction::Call { .. } => "2NNN",
+            Instruction::SkipIfEqual { .. } => "3XNN",
+            Instruction::SkipIfNotEqual { .. } => "4XNN",
+            Instruction::SkipIfRegistersEqual { .. } => "5XY0",
+            Instruction::Set { .. } => "6XNN",
+            Instruction::Add { .. } => "7XNN",
+            Instruction::Copy { .. } => "8XY0",
+            Instruction::Or { .. } => "8XY1",
+            Instruction::And { .. } => "8XY2",
+            Instruction::Xor { .. } => "8XY3",
+            Instruction::AddRegisters { .. } => "8XY4",
+            Instruction::Subtract { .. } => "8XY5",
+            Instruction::ShiftRight { .. } => "8XY6",
+            Instruction::SubtractReversed { .. } => "8XY7",
+            Instruction::ShiftLeft { .. } => "8XYE",
+            Instruction::SkipIfRegistersNotEqual { .. } => "9XY0",
+            Instruction::SetIndex { .. } => "ANNN",
+            Instruction::JumpWithOffset { .. } => "BNNN",
+            Instruction::Random { .. } => "CXNN",
+            Instruction::Draw { .. } => "DXYN",
+            Instruction::SkipIfKeyPressed { .. } => "EX9E",
+            Instruction::SkipIfKeyNotPressed { .. } => "EXA1",
+            Instruction::GetDelayTimer { .. } => "FX07",
+            Instruction::WaitForKey { .. } => "FX0A",
+            Instruction::SetDelayTimer { .. } => "FX15",
+            Instruction::SetSoundTimer { .. } => "FX18",
+            Instruction::AddToIndex { .. } => "FX1E",
+            Instruction::FontCharacter { .. } => "FX29",
+            Instruction::StoreBcd { .. } => "FX33",
+            Instruction::StoreRegisters { .. } => "FX55",
+            Instruction::LoadRegisters { .. } => "FX65",
+            Instruction::Unknown(_) => "????",
+        }
+    }
+    /** Mnemonic as the instruction runs with the given quirks, BNNN jumps relative to VX rather than V0 on SUPER-CHIP */
+    pub fn mnemonic(&self, compatibility: Compatibility) -> String {
+        match (*self, compatibility) {
+            (Instruction::JumpWithOffset { x, nnn }, Compatibility::New) => {
+                format!("JP V{:X}, {:#05X}", x, nnn)
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
+/** Decodes `count` instructions from `memory`, starting at `address` */
+pub fn disassemble(memory: &[u8], address: u16, count: usize) -> Vec<(u16, u16, Instruction)> {
+    (0..count)
+        .map(|i| address as usize + i * 2)
+        .take_while(|&address| address + 1 < memory.len())
+        .map(|address| {
+            let opcode = (memory[address] as u16) << 0x8 | memory[address + 1] as u16;
+            (address as u16, opcode, decode(opcode))
+        })
+        .collect()
+}
+
+/** Mnemonics in the style of Cowgod's Chip-8 Technical Reference, with the original COSMAC VIP quirks, see `mnemonic` for others */
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::ClearScreen => write!(f, "CLS"),
+            Instruction::Return => write!(f, "RET"),
+            Instruction::Jump { nnn } => write!(f, "JP {:#05X}", nnn),
+            Instruction::Call { nnn } => write!(f, "CALL {:#05X}", nnn),
+            Instruction::SkipIfEqual { x, nn } => write!(f, "SE V{:X}, {:#04X}", x, nn),
+            Instruction::SkipIfNotEqual { x, nn } => write!(f, "SNE V{:X}, {:#04X}", x, nn),
+            Instruction::SkipIfRegistersEqual { x, y } => write!(f, "SE V{:X}, V{:X}", x, y),
+            Instruction::Set { x, nn } => write!(f, "LD V{:X}, {:#04X}", x, nn),
+            Instruction::Add { x, nn } => write!(f, "ADD V{:X}, {:#04X}", x, nn),
+            Instruction::Copy { x, y } => write!(f, "LD V{:X}, V{:X}", x, y),
+            Instruction::Or { x, y } => write!(f, "OR V{:X}, V{:X}", x, y),
+            Instruction::And { x, y } => write!(f, "AND V{:X}, V{:X}", x, y),
+            Instruction::Xor { x, y } => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Instruction::AddRegisters { x, y } => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Instruction::Subtract { x, y } => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Instruction::ShiftRight { x, y } => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Instruction::SubtractReversed { x, y } => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::ShiftLeft { x, y } => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Instruction::SkipIfRegistersNotEqual { x, y } => {
+                write!(f, "SNE V{:X}, V{:X}", x, y)
+            }
+            Instruction::SetIndex { nnn } => write!(f, "LD I, {:#05X}", nnn),
+            Instruction::JumpWithOffset { nnn, .. } => write!(f, "JP V0, {:#05X}", nnn),
+            Instruction::Random { x, nn } => write!(f, "RND V{:X}, {:#04X}", x, nn),
+            Instruction::Draw { x, y, n } => write!(f, "DRW V{:X}, V{:X}, {:#03X}", x, y, n),
+            Instruction::SkipIfKeyPressed { x } => write!(f, "SKP V{:X}", x),
+            Instruction::SkipIfKeyNotPressed { x } => write!(f, "SKNP V{:X}", x),
+            Instruction::GetDelayTimer { x } => write!(f, "LD V{:X}, DT", x),
+            Instruction::WaitForKey { x } => write!(f, "LD V{:X}, K", x),
+            Instruction::SetDelayTimer { x } => write!(f, "LD DT, V{:X}", x),
+            Instruction::SetSoundTimer { x } => write!(f, "LD ST, V{:X}", x),
+            Instruction::AddToIndex { x } => write!(f, "ADD I, V{:X}", x),
+            Instruction::FontCharacter { x } => write!(f, "LD F, V{:X}", x),
+            Instruction::StoreBcd { x } => write!(f, "LD B, V{:X}", x),
+            Instruction::StoreRegisters { x } => write!(f, "LD [I], V{:X}", x),
+            Instruction::LoadRegisters { x } => write!(f, "LD V{:X}, [I]", x),
+            Instruction::Unknown(opcode) => write!(f, "DW {:#06X}", opcode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, disassemble, Instruction};
+    use crate::components::processor::Compatibility;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_decode() {
+        // Arrange
+        let opcode: u16 = 0xD123;
+
+        // Act
+        let result = decode(opcode);
+
+        // Assert
+        assert_eq!(result, Instruction::Draw { x: 1, y: 2, n: 3 });
+    }
+    #[wasm_bindgen_test]
+    fn test_decode_nnn() {
+        // Arrange
+        let opcode: u16 = 0xABCD;
+
+        // Act
+        let result = decode(opcode);
+
+        // Assert
+        assert_eq!(result, Instruction::SetIndex { nnn: 0xBCD });
+    }
+    #[wasm_bindgen_test]
+    fn test_decode_unknown() {
+        // Arrange
+        let opcodes: [u16; 5] = [0x0123, 0x5121, 0x8128, 0xE1FF, 0xF1FF];
+
+        // Act
+        let results = opcodes.map(decode);
+
+        // Assert
+        assert_eq!(results, opcodes.map(Instruction::Unknown));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_display() {
+        // Arrange
+        let instructions = [
+            decode(0x00E0),
+            decode(0x2345),
+            decode(0x6A02),
+            decode(0x8AB4),
+            decode(0xD015),
+            decode(0xF265),
+            decode(0xFFFF),
+        ];
+
+        // Act
+        let results = instructions.map(|instruction| instruction.to_string());
+
+        // Assert
+        assert_eq!(
+            results,
+            [
+                "CLS",
+                "CALL 0x345",
+                "LD VA, 0x02",
+                "ADD VA, VB",
+                "DRW V0, V1, 0x5",
+                "LD V2, [I]",
+                "DW 0xFFFF"
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_mnemonic_jump_with_offset() {
+        // Arrange
+        let instruction = decode(0xB345);
+
+        // Act
+        let original = instruction.mnemonic(Compatibility::Original);
+        let new = instruction.mnemonic(Compatibility::New);
+
+        // Assert
+        assert_eq!(original, "JP V0, 0x345");
+        assert_eq!(new, "JP V3, 0x345");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_pattern() {
+        // Arrange
+        let instructions = [decode(0xD123), decode(0x8AB6), decode(0xFFFF)];
+
+        // Act
+        let results = instructions.map(|instruction| instruction.pattern());
+
+        // Assert
+        assert_eq!(results, ["DXYN", "8XY6", "????"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_disassemble() {
+        // Arrange
+        let memory = [0x00, 0xE0, 0x12, 0x00, 0xFF];
+
+        // Act
+        let result = disassemble(&memory, 0x0, 3);
+
+        // Assert
+        assert_eq!(
+            result,
+            vec![
+                (0x0, 0x00E0, Instruction::ClearScreen),
+                (0x2, 0x1200, Instruction::Jump { nnn: 0x200 })
+            ],
+            "Incomplete instruction at the end should be skipped!"
+        );
+    }
+}
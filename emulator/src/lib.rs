@@ -1,45 +1,600 @@
 mod components {
+    pub mod framebuffer;
     pub mod keypad;
     pub mod memory;
+    pub mod platform;
     pub mod processor;
     pub mod screen;
+    pub mod stack;
 }
+pub mod breakpoints;
+pub mod cheats;
+pub mod compare;
+pub mod expression;
+pub mod fault;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod gdb;
+pub mod heatmap;
+pub mod input;
+pub mod instruction;
 pub mod opcodes;
+pub mod profiler;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod reference;
+pub mod snapshot;
+pub mod sprites;
+pub mod state;
+pub mod symbols;
+pub mod trace;
 
-use crate::components::{memory::Memory, *};
-use array_init::array_init;
-use components::processor::Compatibility;
+use crate::components::{
+    memory::{Memory, MemoryError, RomError},
+    stack::Stack,
+    *,
+};
+use breakpoints::{Breakpoint, Condition, LogMessage};
+use cheats::{Cheat, CheatError, CheatFile, Comparison, RamSearch};
+use expression::ExpressionError;
+use heatmap::Heatmap;
+use input::InputScript;
+use instruction::{decode, Instruction};
 use log::*;
+use profiler::Profiler;
+use sprites::{Sprite, SpriteSheet};
+use state::CpuState;
+use symbols::{SymbolError, Symbols};
+use trace::Tracer;
 use wasm_bindgen::prelude::*;
 
+pub use components::{
+    framebuffer::Framebuffer,
+    platform::{ParsePlatformError, Platform},
+    processor::{Compatibility, ParseCompatibilityError},
+};
+
 #[wasm_bindgen]
 #[derive(Debug)]
 pub struct Emulator {
     processor: processor::Processor,
-    screen: screen::Screen,
+    screen: Option<screen::Screen>,
+    platform: Platform,
+    rom: Vec<u8>,
+    tracer: Tracer,
+    seed: Option<u64>,
+    ram_search: RamSearch,
+    cheat_file: CheatFile,
+    /** Cheats for the loaded ROM */
+    cheats: Vec<Cheat>,
+    symbols: Symbols,
 }
 
 #[wasm_bindgen]
 impl Emulator {
     pub fn init(compatibility: Compatibility) -> Emulator {
+        Emulator {
+            screen: Some(screen::Screen::init()),
+            ..Emulator::headless(compatibility)
+        }
+    }
+    /** Emulator without a screen, for batch runs and anything else outside the browser */
+    pub fn headless(compatibility: Compatibility) -> Emulator {
         Emulator {
             processor: processor::Processor::init_compat(compatibility),
-            screen: screen::Screen::init(),
+            screen: None,
+            platform: Platform::Chip8,
+            rom: Vec::new(),
+            tracer: Tracer::init(),
+            seed: None,
+            ram_search: RamSearch::default(),
+            cheat_file: CheatFile::default(),
+            cheats: Vec::new(),
+            symbols: Symbols::default(),
+        }
+    }
+    /** Platform used by the next loaded ROM */
+    pub fn set_platform(&mut self, platform: Platform) {
+        self.platform = platform;
+    }
+    /** Fails without touching the running program if the ROM doesn't fit in memory */
+    pub fn load_rom(&mut self, rom: Vec<u8>) -> Result<(), RomError> {
+        self.processor.memory = self.boot_memory(&rom)?;
+        self.cheats = self.cheat_file.cheats_for(&rom).to_vec();
+        self.rom = rom;
+        self.apply_cheats();
+        self.reset_soft();
+
+        Ok(())
+    }
+    /** Like a power cycle: clears everything, then reloads the fonts and the ROM */
+    pub fn reset_hard(&mut self) -> Result<(), RomError> {
+        self.processor.memory = self.boot_memory(&self.rom)?;
+        self.apply_cheats();
+        self.reset_soft();
+
+        Ok(())
+    }
+    /** Like the COSMAC VIP reset switch: clears registers, timers, stack, display and keypad, but keeps RAM */
+    pub fn reset_soft(&mut self) {
+        self.processor.reset(self.platform.load_address());
+        self.processor.keypad.unset_key();
+        if let Some(seed) = self.seed {
+            self.processor.seed(seed);
         }
     }
-    pub fn load_rom(&mut self, rom: Vec<u8>) {
-        self.processor.pc = Memory::ROM_BEGIN_INDEX;
-        self.processor.gfx = array_init(|_| 0);
-        self.processor.memory.clear();
-        self.processor.memory.load_fonts();
-        self.processor.memory.load_rom(rom);
+    /** Makes random numbers repeat the same way after every reset */
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+        self.processor.seed(seed);
+    }
+
+    pub fn on_key_down(&mut self, code: &str) {
+        if let Some(key) = keypad::key_from_code(code) {
+            self.processor.keypad.set_key(key);
+        }
+    }
+    pub fn on_key_up(&mut self) {
+        self.processor.keypad.unset_key();
     }
 
     pub fn cycle(&mut self) {
-        self.processor.cycle();
+        self.tracer.cycle(&mut self.processor);
+        self.apply_freezes();
+    }
+    /** Runs up to `cycles` cycles in one call, much faster than calling `cycle` repeatedly unless tracing or freezing values */
+    pub fn run(&mut self, cycles: u32) -> u32 {
+        if self.tracer.enabled || self.has_freezes() {
+            self.run_checked(cycles)
+        } else {
+            self.processor.run(cycles)
+        }
+    }
+    /** Executes the instruction at PC, even if there's a breakpoint on it */
+    pub fn step(&mut self) {
+        self.processor.resume();
+        self.cycle();
+    }
+    /**
+     * Like `step`, but runs a whole subroutine if the instruction is a call, stopping when it returns,
+     * at a breakpoint, on a fatal fault or after `max_cycles`. Returns the number of cycles run.
+     */
+    pub fn step_over(&mut self, max_cycles: u32) -> u32 {
+        let return_address = self.processor.pc + 2;
+        let depth = self.processor.stack.len();
+        let call = matches!(decode(self.processor.fetch()), Instruction::Call { .. });
+
+        self.step();
+        let mut cycles = 1;
+        while call && cycles < max_cycles {
+            if self.processor.pc == return_address && self.processor.stack.len() == depth {
+                break;
+            }
+            if self.run(1) == 0 {
+                break;
+            }
+            cycles += 1;
+        }
+
+        cycles
+    }
+    /** Makes the next `cycle` or `run` continue past a breakpoint at PC instead of stopping at it */
+    pub fn resume(&mut self) {
+        self.processor.resume();
     }
     pub fn draw(&self) {
-        self.screen.update(&self.processor.gfx);
+        if let Some(screen) = &self.screen {
+            screen.update(&self.processor.gfx);
+        }
+    }
+
+    /** Number of nested subroutine calls allowed before a stack overflow fault, clears the stack */
+    pub fn set_stack_depth(&mut self, depth: usize) {
+        self.processor.stack = Stack::with_depth(depth);
+    }
+    /** Return addresses currently on the stack, from the oldest call to the newest */
+    pub fn stack(&self) -> Vec<u16> {
+        self.processor.stack.as_slice().to_vec()
+    }
+    /** Current instruction followed by the calls leading to it, newest first, with symbols if loaded */
+    pub fn call_stack(&self) -> Vec<String> {
+        let calls = self.processor.stack.as_slice().iter().rev();
+        std::iter::once(self.processor.pc)
+            .chain(calls.map(|address| address.wrapping_sub(2)))
+            .map(|address| self.symbols.describe(address))
+            .collect()
+    }
+    /** Disassembly of `count` instructions starting at `address`, one line per instruction, annotated with symbols if loaded */
+    pub fn disassemble(&self, address: u16, count: usize) -> Vec<String> {
+        instruction::disassemble(self.processor.memory.data(), address, count)
+            .iter()
+            .map(|(address, opcode, instruction)| {
+                let line = format!(
+                    "{:#06X}: {:04X}  {}",
+                    address,
+                    opcode,
+                    instruction.mnemonic(self.processor.compatibility)
+                );
+                match self.symbols.annotate(*address, instruction).as_str() {
+                    "" => line,
+                    annotation => format!("{:<32}; {}", line, annotation),
+                }
+            })
+            .collect()
+    }
+
+    /** Loads labels and source lines, see `Symbols` for the format */
+    pub fn load_symbols(&mut self, text: &str) -> Result<(), SymbolError> {
+        self.symbols = Symbols::parse(text)?;
+        Ok(())
+    }
+    pub fn clear_symbols(&mut self) {
+        self.symbols = Symbols::default();
+    }
+    /** Address with its label and source line if symbols are loaded, e.g. `0x02A4 <draw_player> (game.8o:40)` */
+    pub fn describe_address(&self, address: u16) -> String {
+        self.symbols.describe(address)
+    }
+    /** Address of a label or `file:line` from the loaded symbols */
+    pub fn resolve_address(&self, name: &str) -> Option<u16> {
+        self.symbols.resolve(name)
+    }
+
+    /** Makes `cycle` and `run` stop before executing the instruction at `address` */
+    pub fn add_breakpoint(&mut self, address: u16) {
+        self.processor
+            .breakpoints
+            .insert(address, Breakpoint::default());
+    }
+    /** Breakpoint which only stops when `condition` holds, e.g. `v3 == 0x10 && i > 0x300` or `hit_count > 5` */
+    pub fn add_conditional_breakpoint(
+        &mut self,
+        address: u16,
+        condition: &str,
+    ) -> Result<(), ExpressionError> {
+        let breakpoint = Breakpoint {
+            condition: Some(Condition::parse(condition)?),
+            ..Breakpoint::default()
+        };
+        self.processor.breakpoints.insert(address, breakpoint);
+        Ok(())
+    }
+    /** Logs `message` instead of stopping, e.g. `V3 is {v3}, I is {i:x}`, only when `condition` holds if given */
+    pub fn add_logpoint(
+        &mut self,
+        address: u16,
+        message: &str,
+        condition: Option<String>,
+    ) -> Result<(), ExpressionError> {
+        let logpoint = Breakpoint {
+            condition: condition.as_deref().map(Condition::parse).transpose()?,
+            log_message: Some(LogMessage::parse(message)?),
+            ..Breakpoint::default()
+        };
+        self.processor.breakpoints.insert(address, logpoint);
+        Ok(())
+    }
+    /** Adds a breakpoint at a label or `file:line` from the loaded symbols, returns its address if found */
+    pub fn add_breakpoint_at(&mut self, name: &str) -> Option<u16> {
+        let address = self.resolve_address(name)?;
+        self.add_breakpoint(address);
+
+        Some(address)
+    }
+    pub fn has_breakpoint(&self, address: u16) -> bool {
+        self.processor.breakpoints.is_armed(address)
+    }
+    pub fn remove_breakpoint(&mut self, address: u16) {
+        self.processor.breakpoints.remove(address);
+    }
+    pub fn clear_breakpoints(&mut self) {
+        self.processor.breakpoints.clear();
+    }
+    /** Every breakpoint with its condition, message and hits, described with symbols if loaded */
+    pub fn breakpoints(&self) -> Vec<String> {
+        self.processor
+            .breakpoints
+            .iter()
+            .map(|(&address, breakpoint)| {
+                format!("{}: {}", self.symbols.describe(address), breakpoint)
+            })
+            .collect()
+    }
+    /** Address of the breakpoint which stopped the last `cycle` or `run`, if any */
+    pub fn hit_breakpoint(&self) -> Option<u16> {
+        self.processor.hit_breakpoint
+    }
+    /** Logpoint output since the last call, oldest first */
+    pub fn logpoint_output(&mut self) -> Vec<String> {
+        self.processor.breakpoints.take_log()
+    }
+    /** Starts recording executed instructions, keeping only the last `capacity` of them */
+    pub fn start_trace(&mut self, capacity: usize) {
+        self.tracer.start(capacity);
+    }
+    pub fn stop_trace(&mut self) {
+        self.tracer.stop();
+    }
+    /** Only records instructions in `start..=end` whose highest nibble has its bit set in `opcode_classes` */
+    pub fn set_trace_filter(&mut self, start: u16, end: u16, opcode_classes: u16) {
+        self.tracer.filter = trace::TraceFilter {
+            addresses: start..=end,
+            opcode_classes,
+        };
+    }
+    /** Recorded trace, one line per instruction, annotated with symbols if loaded */
+    pub fn trace_text(&self) -> String {
+        if self.symbols.is_empty() {
+            return self.tracer.to_text();
+        }
+
+        self.tracer
+            .entries()
+            .map(|entry| {
+                let mut comment = self.symbols.annotate(entry.pc, &decode(entry.opcode));
+                if comment.is_empty() {
+                    comment = self.symbols.label(entry.pc).unwrap_or_default();
+                }
+                match comment.as_str() {
+                    "" => format!("{}\n", entry),
+                    _ => format!("{} ; {}\n", entry, comment),
+                }
+            })
+            .collect()
+    }
+    pub fn trace_json_lines(&self) -> String {
+        self.tracer.to_json_lines()
+    }
+
+    /** Up to `length` bytes starting at `address`, cut short at the end of memory */
+    pub fn read_memory(&self, address: u16, length: usize) -> Vec<u8> {
+        self.processor.memory.read(address, length).to_vec()
+    }
+    /** Writes bytes starting at `address`, taking effect even if they overwrite running code */
+    pub fn write_memory(&mut self, address: u16, bytes: Vec<u8>) -> Result<(), MemoryError> {
+        self.processor.memory.write_bytes(address, &bytes)
+    }
+    pub fn fill_memory(
+        &mut self,
+        address: u16,
+        length: usize,
+        value: u8,
+    ) -> Result<(), MemoryError> {
+        self.processor.memory.fill(address, length, value)
+    }
+    /** Addresses of every occurrence of `pattern` in memory */
+    pub fn find_memory(&self, pattern: Vec<u8>) -> Vec<u16> {
+        self.processor.memory.find(&pattern)
+    }
+    /** Hexdump of `length` bytes starting at `address`, annotated with the font, ROM, stack and display areas */
+    pub fn hexdump(&self, address: u16, length: usize) -> String {
+        self.processor
+            .memory
+            .hexdump(address, length, &self.rom_range())
+    }
+
+    /** Starts a new RAM search, every address is a candidate until narrowed */
+    pub fn start_ram_search(&mut self) {
+        self.ram_search.start(&self.processor.memory);
+    }
+    /** Drops candidates failing the comparison against the last search, `value` is used by `Equal` and `NotEqual` */
+    pub fn narrow_ram_search(&mut self, comparison: Comparison, value: u8) -> usize {
+        self.ram_search
+            .narrow(&self.processor.memory, comparison, value)
+    }
+    pub fn ram_search_candidates(&self) -> Vec<u16> {
+        self.ram_search.candidates().to_vec()
+    }
+
+    /** Keeps `address` at `value` for as long as the ROM is loaded */
+    pub fn add_freeze(&mut self, address: u16, value: u8) -> Result<(), MemoryError> {
+        self.add_cheat(Cheat::Freeze { address, value })
+    }
+    /** Writes `bytes` at `address` now and after every reload of the ROM */
+    pub fn add_patch(&mut self, address: u16, bytes: Vec<u8>) -> Result<(), MemoryError> {
+        self.add_cheat(Cheat::Patch { address, bytes })
+    }
+    /** Removes the cheats of the loaded ROM, patched memory stays until the next hard reset */
+    pub fn clear_cheats(&mut self) {
+        self.cheats.clear();
+        self.cheat_file.insert(&self.rom, Vec::new());
+    }
+    /** Loads a cheat file, applying the cheats for the loaded ROM right away, returns how many there are */
+    pub fn load_cheats(&mut self, text: &str) -> Result<usize, CheatError> {
+        self.cheat_file = CheatFile::parse(text)?;
+        self.cheats = self.cheat_file.cheats_for(&self.rom).to_vec();
+        self.apply_cheats();
+
+        Ok(self.cheats.len())
+    }
+    /** Cheat file with the cheats for every ROM, including ones added since loading it */
+    pub fn export_cheats(&self) -> String {
+        self.cheat_file.to_string()
+    }
+    /** SHA-1 of the loaded ROM, which cheat files are keyed by */
+    pub fn rom_hash(&self) -> String {
+        cheats::rom_hash(&self.rom)
+    }
+
+    /** Starts counting executions and data reads from scratch */
+    pub fn start_profiling(&mut self) {
+        self.processor.profiler = Some(Profiler::init());
+    }
+    pub fn stop_profiling(&mut self) {
+        self.processor.profiler = None;
+    }
+    /** The `count` most executed instructions followed by executions per opcode, empty when not profiling */
+    pub fn hotspot_report(&self, count: usize) -> String {
+        match &self.processor.profiler {
+            Some(profiler) => profiler.report(
+                self.processor.memory.data(),
+                self.processor.compatibility,
+                count,
+            ),
+            None => String::new(),
+        }
+    }
+    /** Use of every address: 0 unused, 1 code, 2 data read by DXYN or FX65, 3 both */
+    pub fn coverage_map(&self) -> Vec<u8> {
+        match &self.processor.profiler {
+            Some(profiler) => profiler
+                .coverage()
+                .iter()
+                .map(|&coverage| coverage as u8)
+                .collect(),
+            None => vec![0; Memory::SIZE],
+        }
+    }
+
+    /** Starts tracking memory accesses, which fade to half brightness after `half_life` cycles */
+    pub fn start_heatmap(&mut self, half_life: u32) {
+        self.processor.heatmap = Some(Heatmap::init(half_life as u64));
+    }
+    pub fn stop_heatmap(&mut self) {
+        self.processor.heatmap = None;
+    }
+    /** 64x64 RGBA image with a pixel per byte of memory: red for writes, green for reads and blue for executes */
+    pub fn heatmap_image(&self) -> Vec<u8> {
+        match &self.processor.heatmap {
+            Some(heatmap) => heatmap.image(),
+            None => Heatmap::init(1).image(),
+        }
+    }
+
+    /** Renders `count` sprites stored one after another from `address`, a `height` of 0 renders 16x16 SCHIP sprites */
+    pub fn sprite_sheet(
+        &self,
+        address: u16,
+        count: usize,
+        height: u8,
+        columns: usize,
+    ) -> SpriteSheet {
+        let sprites = sprites::sprites_in_range(address, count, height);
+        SpriteSheet::render(self.processor.memory.data(), &sprites, columns)
+    }
+    /** Renders every sprite drawn since the last reset, in the order of their addresses */
+    pub fn drawn_sprite_sheet(&self, columns: usize) -> SpriteSheet {
+        SpriteSheet::render(self.processor.memory.data(), &self.drawn_sprites(), columns)
+    }
+    /** Addresses of every sprite drawn since the last reset */
+    pub fn drawn_sprite_addresses(&self) -> Vec<u16> {
+        self.drawn_sprites()
+            .iter()
+            .map(|sprite| sprite.address)
+            .collect()
+    }
+
+    /** Snapshot of registers, timers, stack and the instruction about to run */
+    pub fn state(&self) -> CpuState {
+        CpuState::capture(&self.processor)
+    }
+    /** Description of the fault which halted the processor, if any */
+    pub fn fault(&self) -> Option<String> {
+        self.processor
+            .fault
+            .map(|fault| format!("{} PC: {:#06X}", fault, self.processor.pc))
+    }
+}
+impl Emulator {
+    pub const CYCLES_PER_FRAME: u32 = processor::Processor::CYCLES_PER_FRAME;
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.processor.gfx
+    }
+    /** Holds down a key, 0x0 to 0xF, until `on_key_up` */
+    pub fn press_key(&mut self, key: u8) {
+        self.processor.keypad.set_key(key & 0xF);
+    }
+    /** Presses and releases keys the script has for the current cycle */
+    pub fn apply_inputs(&mut self, inputs: &InputScript) {
+        inputs.apply(self.processor.cycles, &mut self.processor.keypad);
+    }
+    /** Whether the program stopped for good, on a fatal fault or on a jump to itself which is how most programs end */
+    pub fn is_halted(&self) -> bool {
+        let pc = self.processor.pc;
+        self.processor.fault.is_some()
+            || decode(self.processor.fetch()) == Instruction::Jump { nnn: pc }
+    }
+
+    pub fn tracer(&self) -> &Tracer {
+        &self.tracer
+    }
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.processor.profiler.as_ref()
+    }
+
+    /** Slower run which records traces, stops like `Processor::run` */
+    fn run_checked(&mut self, cycles: u32) -> u32 {
+        for cycle in 0..cycles {
+            if self.processor.fault.is_some() {
+                return cycle;
+            }
+            self.tracer.cycle(&mut self.processor);
+            self.apply_freezes();
+            if self.processor.hit_breakpoint.is_some() {
+                return cycle;
+            }
+        }
+        cycles
+    }
+
+    fn add_cheat(&mut self, cheat: Cheat) -> Result<(), MemoryError> {
+        let (Cheat::Freeze { address, .. } | Cheat::Patch { address, .. }) = cheat;
+        if !cheat.fits() {
+            return Err(MemoryError::OutOfBounds {
+                address: address as usize,
+                length: cheat.size(),
+            });
+        }
+
+        cheat.apply(&mut self.processor.memory);
+        self.cheats.push(cheat);
+        self.cheat_file.insert(&self.rom, self.cheats.clone());
+        Ok(())
+    }
+    fn apply_cheats(&mut self) {
+        for cheat in &self.cheats {
+            cheat.apply(&mut self.processor.memory);
+        }
+    }
+    fn has_freezes(&self) -> bool {
+        self.cheats
+            .iter()
+            .any(|cheat| matches!(cheat, Cheat::Freeze { .. }))
+    }
+    fn apply_freezes(&mut self) {
+        for cheat in &self.cheats {
+            if let Cheat::Freeze { .. } = cheat {
+                cheat.apply(&mut self.processor.memory);
+            }
+        }
+    }
+
+    pub fn drawn_sprites(&self) -> Vec<Sprite> {
+        self.processor
+            .drawn_sprites
+            .iter()
+            .enumerate()
+            .filter_map(|(address, height)| {
+                height.map(|height| Sprite {
+                    address: address as u16,
+                    height,
+                })
+            })
+            .collect()
+    }
+
+    /** Addresses the current ROM was loaded to */
+    fn rom_range(&self) -> std::ops::Range<u16> {
+        let begin = self.platform.load_address();
+        begin..begin + self.rom.len() as u16
+    }
+
+    /** Fresh memory with fonts and the ROM loaded at the platform's address */
+    fn boot_memory(&self, rom: &[u8]) -> Result<Memory, RomError> {
+        let mut memory = Memory::init();
+        memory.load_fonts();
+        memory.load_rom(rom, self.platform.load_address())?;
+
+        Ok(memory)
     }
 }
 
@@ -49,33 +604,226 @@ pub fn init() {
     console_log::init_with_level(Level::Warn).expect("Failed initializing logger!");
 }
 
-#[wasm_bindgen]
-pub fn on_key_down(code: &str) {
-    let key = match code {
-        "Digit1" => 0x1,
-        "Digit2" => 0x2,
-        "Digit3" => 0x3,
-        "Digit4" => 0xC,
-        "KeyQ" => 0x4,
-        "KeyW" => 0x5,
-        "KeyE" => 0x6,
-        "KeyR" => 0xD,
-        "KeyA" => 0x7,
-        "KeyS" => 0x8,
-        "KeyD" => 0x9,
-        "KeyF" => 0xE,
-        "KeyZ" => 0xA,
-        "KeyX" => 0x0,
-        "KeyC" => 0xB,
-        "KeyV" => 0xF,
-        _ => 0x0,
-    };
-
-    if key != 0x0 {
-        keypad::INSTANCE.lock().unwrap().set_key(key);
+#[cfg(test)]
+mod tests {
+    use super::{Compatibility, Emulator};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const SYMBOLS: &str = "0x200 main\n0x204 loop\n0x300 draw\n0x300 game.8o:20\n";
+
+    fn emulator_with_rom(rom: &[u8]) -> Emulator {
+        let mut emulator = Emulator::headless(Compatibility::Original);
+        emulator.load_rom(rom.to_vec()).unwrap();
+        emulator.load_symbols(SYMBOLS).unwrap();
+
+        emulator
+    }
+
+    #[wasm_bindgen_test]
+    fn test_run_breakpoint() {
+        // Arrange
+        // 0x200: V0 += 1, 0x202: V1 += 1, 0x204: jump to 0x200
+        let mut emulator = emulator_with_rom(&[0x70, 0x01, 0x71, 0x01, 0x12, 0x00]);
+        emulator.add_breakpoint_at("loop");
+
+        // Act
+        let first = emulator.run(100);
+        let second = emulator.run(100);
+
+        // Assert
+        assert_eq!(first, 2);
+        assert_eq!(
+            second, 3,
+            "Run should continue past the breakpoint it stopped at!"
+        );
+        assert_eq!(emulator.hit_breakpoint(), Some(0x204));
+        assert_eq!(emulator.breakpoints(), ["0x0204 <loop>: hits 2"]);
+    }
+    #[wasm_bindgen_test]
+    fn test_run_conditional_breakpoint_traced() {
+        // Arrange
+        // 0x200: V0 += 1, 0x202: V1 += 1, 0x204: jump to 0x200
+        let mut emulator = emulator_with_rom(&[0x70, 0x01, 0x71, 0x01, 0x12, 0x00]);
+        emulator.start_trace(100);
+        emulator
+            .add_conditional_breakpoint(0x204, "v0 == 2 && [0x201] != 0")
+            .unwrap();
+        emulator.add_logpoint(0x202, "V0 is {v0}", None).unwrap();
+
+        // Act
+        let ran = emulator.run(100);
+
+        // Assert
+        assert_eq!(ran, 5);
+        assert_eq!(emulator.hit_breakpoint(), Some(0x204));
+        assert_eq!(emulator.tracer().len(), 5);
+        assert_eq!(emulator.logpoint_output(), ["V0 is 1", "V0 is 2"]);
+        assert_eq!(
+            emulator.breakpoints(),
+            [
+                "0x0202 <main+0x2>: log \"V0 is {v0}\", hits 2",
+                "0x0204 <loop>: if v0 == 2 && [0x201] != 0, hits 2"
+            ]
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_add_conditional_breakpoint_error() {
+        // Arrange
+        let mut emulator = emulator_with_rom(&[]);
+
+        // Act
+        let result = emulator.add_conditional_breakpoint(0x200, "v0 ==");
+
+        // Assert
+        assert!(result.is_err());
+        assert!(emulator.breakpoints().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_step_over() {
+        // Arrange
+        // 0x200: call 0x300, 0x202: V1 += 1, 0x300: V0 += 1, 0x302: return
+        let mut emulator = emulator_with_rom(&[0x23, 0x00, 0x71, 0x01]);
+        emulator
+            .write_memory(0x300, vec![0x70, 0x01, 0x00, 0xEE])
+            .unwrap();
+        emulator.add_breakpoint(0x200);
+
+        // Act
+        let call = emulator.step_over(100);
+        let add = emulator.step_over(100);
+
+        // Assert
+        assert_eq!(call, 3, "Should run the whole subroutine!");
+        assert_eq!(add, 1);
+        assert_eq!(emulator.state().pc, 0x204);
+        assert_eq!(emulator.state().v[..2], [0x1, 0x1]);
+    }
+    #[wasm_bindgen_test]
+    fn test_step_over_stops_at_breakpoint() {
+        // Arrange
+        // 0x200: call 0x300, 0x300: V0 += 1, 0x302: return
+        let mut emulator = emulator_with_rom(&[0x23, 0x00]);
+        emulator
+            .write_memory(0x300, vec![0x70, 0x01, 0x00, 0xEE])
+            .unwrap();
+        emulator.add_breakpoint(0x302);
+
+        // Act
+        let result = emulator.step_over(100);
+
+        // Assert
+        assert_eq!(result, 2);
+        assert_eq!(emulator.hit_breakpoint(), Some(0x302));
+        assert!(emulator.has_breakpoint(0x302));
+        assert!(!emulator.has_breakpoint(0x300));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_reset_soft() {
+        // Arrange
+        // 0x200: V0 += 1, 0x202: V1 += 1, 0x204: jump to 0x200
+        let mut emulator = emulator_with_rom(&[0x70, 0x01, 0x71, 0x01, 0x12, 0x00]);
+        emulator.write_memory(0x300, vec![0xAB]).unwrap();
+        emulator.add_breakpoint_at("loop");
+        emulator.cycle();
+
+        // Act
+        emulator.reset_soft();
+
+        // Assert
+        let state = emulator.state();
+        assert_eq!(state.pc, 0x200);
+        assert_eq!(state.v[0x0], 0x0);
+        assert_eq!(emulator.read_memory(0x200, 2), [0x70, 0x01]);
+        assert_eq!(
+            emulator.read_memory(0x300, 1),
+            [0xAB],
+            "RAM should be kept!"
+        );
+        assert_eq!(emulator.resolve_address("loop"), Some(0x204));
+        assert!(emulator.has_breakpoint(0x204));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_reset_hard() {
+        // Arrange
+        // 0x200: return without a call
+        let mut emulator = emulator_with_rom(&[0x00, 0xEE]);
+        emulator.add_patch(0x300, vec![0xCD]).unwrap();
+        emulator.clear_cheats();
+        emulator.write_memory(0x202, vec![0xAB]).unwrap();
+        emulator.cycle();
+        assert!(emulator.fault().is_some());
+
+        // Act
+        emulator.reset_hard().unwrap();
+
+        // Assert
+        let state = emulator.state();
+        assert_eq!(state.pc, 0x200);
+        assert!(!state.halted);
+        assert_eq!(emulator.fault(), None);
+        assert_eq!(emulator.read_memory(0x200, 3), [0x00, 0xEE, 0x00]);
+        assert_eq!(
+            emulator.read_memory(0x300, 1),
+            [0x00],
+            "Cleared cheats shouldn't be patched back in!"
+        );
+        assert_eq!(emulator.resolve_address("loop"), Some(0x204));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_run_freeze() {
+        // Arrange
+        // 0x200: I = 0x300, 0x202: V0 = 0, 0x204: [I] = V0, 0x206: I = 0x300, 0x208: V0 = [I]
+        let mut emulator =
+            emulator_with_rom(&[0xA3, 0x00, 0x60, 0x00, 0xF0, 0x55, 0xA3, 0x00, 0xF0, 0x65]);
+        emulator.add_freeze(0x300, 0x03).unwrap();
+
+        // Act
+        let ran = emulator.run(5);
+
+        // Assert
+        assert_eq!(ran, 5);
+        assert_eq!(
+            emulator.state().v[0x0],
+            0x03,
+            "Frozen values should be restored after every cycle of a batch!"
+        );
+        assert_eq!(emulator.read_memory(0x300, 1), [0x03]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_call_stack() {
+        // Arrange
+        // 0x200: call 0x300, 0x300: clear the screen
+        let mut emulator = emulator_with_rom(&[0x23, 0x00]);
+        emulator.write_memory(0x300, vec![0x00, 0xE0]).unwrap();
+        emulator.cycle();
+
+        // Act
+        let result = emulator.call_stack();
+
+        // Assert
+        assert_eq!(result, ["0x0300 <draw> (game.8o:20)", "0x0200 <main>"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_disassemble_symbols() {
+        // Arrange
+        let emulator = emulator_with_rom(&[0x23, 0x00, 0x70, 0x01]);
+
+        // Act
+        let result = emulator.disassemble(0x200, 2);
+
+        // Assert
+        assert_eq!(
+            result,
+            [
+                "0x0200: 2300  CALL 0x300        ; main: -> draw",
+                "0x0202: 7001  ADD V0, 0x01"
+            ]
+        );
     }
-}
-#[wasm_bindgen]
-pub fn on_key_up() {
-    keypad::INSTANCE.lock().unwrap().unset_key();
 }
@@ -1,10 +1,10 @@
 use crate::components::memory::Memory;
 
-use super::keypad;
 use super::processor::{Compatibility, Processor};
 use super::screen::Screen;
-use array_init::array_init;
+use crate::fault::Fault;
 use log::*;
+use rand::Rng;
 
 pub struct OpCode00E0;
 pub struct OpCode00EE;
@@ -42,375 +42,451 @@ pub struct OpCodeFX1E;
 pub struct OpCodeFX55;
 pub struct OpCodeFX65;
 
+/** Start of the `length` bytes at I, if they're all inside memory */
+fn index_range(processor: &Processor, length: usize) -> Result<usize, Fault> {
+    let start = processor.i as usize;
+    if start >= Memory::SIZE || start + length > Memory::SIZE {
+        return Err(Fault::MemoryOutOfBounds {
+            address: start.max(Memory::SIZE),
+        });
+    }
+
+    Ok(start)
+}
+
 pub trait OpCode {
-    fn execute(processor: &mut Processor, data: &[u16]);
+    /** Register indices and immediate values the opcode works on */
+    type Operands;
+
+    fn execute(processor: &mut Processor, operands: Self::Operands) -> Result<(), Fault>;
 }
 
 impl OpCode for OpCode00E0 {
-    fn execute(processor: &mut Processor, _: &[u16]) {
-        processor.gfx = array_init(|_| 0);
+    type Operands = ();
+
+    fn execute(processor: &mut Processor, _: Self::Operands) -> Result<(), Fault> {
+        processor.gfx.clear();
+
+        Ok(())
     }
 }
 impl OpCode for OpCode00EE {
-    fn execute(processor: &mut Processor, _: &[u16]) {
-        let return_address = processor.stack.pop().expect("Stack is empty!");
+    type Operands = ();
+
+    fn execute(processor: &mut Processor, _: Self::Operands) -> Result<(), Fault> {
+        let return_address = processor.stack.pop()?;
         processor.pc = return_address;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode1NNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        processor.pc = data[0];
+    type Operands = u16;
+
+    fn execute(processor: &mut Processor, nnn: Self::Operands) -> Result<(), Fault> {
+        processor.pc = nnn;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode2NNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let nnn = data[0];
-        processor.stack.push(processor.pc);
+    type Operands = u16;
+
+    fn execute(processor: &mut Processor, nnn: Self::Operands) -> Result<(), Fault> {
+        processor.stack.push(processor.pc)?;
         processor.pc = nnn;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode3XNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let nn = data[1] as u8;
+    type Operands = (usize, u8);
 
+    fn execute(processor: &mut Processor, (x, nn): Self::Operands) -> Result<(), Fault> {
         if processor.v[x] == nn {
             processor.pc += 2;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCode4XNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let nn = data[1] as u8;
+    type Operands = (usize, u8);
 
+    fn execute(processor: &mut Processor, (x, nn): Self::Operands) -> Result<(), Fault> {
         if processor.v[x] != nn {
             processor.pc += 2;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCode5XY0 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
 
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         if processor.v[x] == processor.v[y] {
             processor.pc += 2;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCode6XNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let nn = data[1] as u8;
+    type Operands = (usize, u8);
+
+    fn execute(processor: &mut Processor, (x, nn): Self::Operands) -> Result<(), Fault> {
         processor.v[x] = nn;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode7XNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let nn = data[1] as u8;
-        processor.v[x] = processor.v[x].wrapping_add(nn)
+    type Operands = (usize, u8);
+
+    fn execute(processor: &mut Processor, (x, nn): Self::Operands) -> Result<(), Fault> {
+        processor.v[x] = processor.v[x].wrapping_add(nn);
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY0 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
+
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         processor.v[x] = processor.v[y];
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY1 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
+
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         processor.v[x] |= processor.v[y];
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY2 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
+
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         processor.v[x] &= processor.v[y];
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY3 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
+
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         processor.v[x] ^= processor.v[y];
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY4 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
+
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         let (result, overflow) = processor.v[x].overflowing_add(processor.v[y]);
 
         processor.v[x] = result;
         processor.v[0xF] = overflow as u8;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY5 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
+
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         let (result, overflow) = processor.v[x].overflowing_sub(processor.v[y]);
 
         processor.v[x] = result;
         processor.v[0xF] = !overflow as u8;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY6 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
 
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         if processor.compatibility == Compatibility::Original {
             processor.v[x] = processor.v[y];
         }
-        processor.v[0xF] = processor.v[x] & 0x1;
+        // The flag goes in last, so it's what's left when VF is the destination
+        let flag = processor.v[x] & 0x1;
         processor.v[x] >>= 1;
+        processor.v[0xF] = flag;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XY7 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
+
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         let (result, overflow) = processor.v[y].overflowing_sub(processor.v[x]);
 
         processor.v[x] = result;
         processor.v[0xF] = !overflow as u8;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode8XYE {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
 
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         if processor.compatibility == Compatibility::Original {
             processor.v[x] = processor.v[y];
         }
-        processor.v[0xF] = (processor.v[x] & 0x80) >> 7;
+        let flag = (processor.v[x] & 0x80) >> 7;
         processor.v[x] <<= 1;
+        processor.v[0xF] = flag;
+
+        Ok(())
     }
 }
 impl OpCode for OpCode9XY0 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
+    type Operands = (usize, usize);
 
+    fn execute(processor: &mut Processor, (x, y): Self::Operands) -> Result<(), Fault> {
         if processor.v[x] != processor.v[y] {
             processor.pc += 2;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeANNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        processor.i = data[0];
+    type Operands = u16;
+
+    fn execute(processor: &mut Processor, nnn: Self::Operands) -> Result<(), Fault> {
+        processor.i = nnn;
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeBNNN {
-    // BXNN for newer systems
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        if processor.compatibility == Compatibility::New {
-            panic!("BXNN is not supported on newer systems!");
-        }
+    type Operands = u16;
 
-        let nnn = data[0];
+    // BXNN for newer systems
+    fn execute(processor: &mut Processor, nnn: Self::Operands) -> Result<(), Fault> {
         processor.pc = nnn + processor.v[0] as u16;
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeBXNN {
-    // BXNN for original systems
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        if processor.compatibility == Compatibility::Original {
-            panic!("BXNN is not supported on original systems!");
-        }
+    type Operands = (usize, u16);
 
-        let x = data[0] as usize;
-        let nnn = data[1]; // X is included
+    // BNNN for original systems
+    fn execute(processor: &mut Processor, (x, nnn): Self::Operands) -> Result<(), Fault> {
         processor.pc = nnn + processor.v[x] as u16;
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeCXNN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let nn = data[1];
-        let random = rand::random::<u8>();
+    type Operands = (usize, u8);
+
+    fn execute(processor: &mut Processor, (x, nn): Self::Operands) -> Result<(), Fault> {
+        let random = processor.rng.gen::<u8>();
+
+        processor.v[x] = random & nn;
 
-        processor.v[x] = random & nn as u8;
+        Ok(())
     }
 }
 impl OpCode for OpCodeDXYN {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let y = data[1] as usize;
-        let n = data[2];
+    type Operands = (usize, usize, u8);
 
+    fn execute(processor: &mut Processor, (x, y, n): Self::Operands) -> Result<(), Fault> {
         let sprite_x = processor.v[x] as usize % Screen::WIDTH;
         let sprite_y = processor.v[y] as usize % Screen::HEIGHT;
         let height = n as usize;
-        let width = 8;
-        let mut flipped = false;
+        let address = index_range(processor, height.max(1))?;
+        processor.drawn_sprites[address] = Some(n);
 
-        for row in 0..height {
-            let sprite = processor.memory.data[processor.i as usize + row];
-            debug!("Row {:#02}: {:#010b}", row, sprite);
+        let sprite = &processor.memory.data()[address..address + height];
+        debug!("Sprite: {:02X?}", sprite);
 
-            for col in 0..width {
-                let sprite_bit = (sprite >> (width - 1 - col)) & 0x1;
-                let gfx_i = (sprite_y + row) * Screen::WIDTH + (sprite_x + col);
-
-                if gfx_i >= processor.gfx.len() {
-                    continue;
-                }
-
-                let prev_gfx = processor.gfx[gfx_i];
-                processor.gfx[gfx_i] ^= sprite_bit;
-
-                if prev_gfx == 1 && processor.gfx[gfx_i] == 0 {
-                    flipped = true;
-                }
-            }
-        }
+        // Clip at the edges, only the starting position wraps around
+        let flipped = processor.gfx.draw(sprite_x, sprite_y, sprite);
 
         processor.v[0xF] = flipped as u8;
         debug!("Flipped: {}", flipped);
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeEX9E {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let keypad = keypad::INSTANCE.lock().unwrap();
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
+        let keypad = &processor.keypad;
 
         if keypad.is_key_pressed() && processor.v[x] == keypad.get_current_key() {
             processor.pc += 2;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeEXA1 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let keypad = keypad::INSTANCE.lock().unwrap();
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
+        let keypad = &processor.keypad;
 
         if (keypad.is_key_pressed() && processor.v[x] != keypad.get_current_key())
             || !keypad.is_key_pressed()
         {
             processor.pc += 2;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX07 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
         processor.v[x] = processor.delay_timer;
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX0A {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let keypad = keypad::INSTANCE.lock().unwrap();
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
+        let keypad = &processor.keypad;
 
         if !keypad.is_key_pressed() {
             processor.pc -= 2;
         } else {
             processor.v[x] = keypad.get_current_key();
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX15 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
         processor.delay_timer = processor.v[x];
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX18 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
         processor.sound_timer = processor.v[x];
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX1E {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
         processor.i = processor.i.wrapping_add(processor.v[x] as u16);
 
         if processor.i > 0x0FFF {
             processor.v[0xF] = 1;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX29 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
-        let digit = processor.v[x] as usize;
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
+        let digit = (processor.v[x] & 0xF) as usize;
         processor.i = Memory::FONT_BEGIN_INDEX + (digit * 5) as u16;
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX33 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
+    type Operands = usize;
+
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
         let value = processor.v[x];
 
         let ones = value % 10;
         let tens = (value / 10) % 10;
         let hundreds = value / 100;
+        index_range(processor, 3)?;
+
+        processor.memory.write(processor.i, hundreds);
+        processor.memory.write(processor.i + 1, tens);
+        processor.memory.write(processor.i + 2, ones);
 
-        processor.memory.data[processor.i as usize] = hundreds;
-        processor.memory.data[processor.i as usize + 1] = tens;
-        processor.memory.data[processor.i as usize + 2] = ones;
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX55 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
+    type Operands = usize;
 
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
+        let address = index_range(processor, x + 1)?;
         for i in 0..=x {
-            processor.memory.data[processor.i as usize + i] = processor.v[i];
-            if processor.compatibility == Compatibility::Original {
-                processor.i += 1;
-            }
+            processor.memory.write((address + i) as u16, processor.v[i]);
+        }
+        if processor.compatibility == Compatibility::Original {
+            processor.i += x as u16 + 1;
         }
+
+        Ok(())
     }
 }
 impl OpCode for OpCodeFX65 {
-    fn execute(processor: &mut Processor, data: &[u16]) {
-        let x = data[0] as usize;
+    type Operands = usize;
 
-        for i in 0..=x {
-            processor.v[i] = processor.memory.data[processor.i as usize + i];
-            if processor.compatibility == Compatibility::Original {
-                processor.i += 1;
-            }
+    fn execute(processor: &mut Processor, x: Self::Operands) -> Result<(), Fault> {
+        let address = index_range(processor, x + 1)?;
+        processor.v[..=x].copy_from_slice(&processor.memory.data()[address..=address + x]);
+        if processor.compatibility == Compatibility::Original {
+            processor.i += x as u16 + 1;
         }
+
+        Ok(())
     }
 }
 
 #[allow(non_snake_case)]
 mod tests {
     use super::*;
+    use crate::components::framebuffer::Framebuffer;
     use crate::components::memory::Memory;
-    use array_init::array_init;
+    use crate::instruction::decode;
     use wasm_bindgen_test::wasm_bindgen_test;
 
     fn execute_instruction(processor: &mut Processor, instruction: u16) {
-        processor
-            .execute((instruction & 0xF000) >> 0xC, instruction & 0x0FFF)
-            .unwrap();
+        processor.execute(decode(instruction)).unwrap();
     }
 
     #[wasm_bindgen_test]
     fn test_00E0() {
         // Arrange
         let mut processor = Processor::init();
-        processor.gfx = array_init(|_| 1);
+        processor.gfx = Framebuffer::from_bytes(&[1; Screen::WIDTH * Screen::HEIGHT]);
 
         // Act
         execute_instruction(&mut processor, 0x00E0);
 
         // Assert
-        assert_eq!(processor.gfx, array_init(|_| 0));
+        assert_eq!(processor.gfx, Framebuffer::init());
     }
 
     #[wasm_bindgen_test]
@@ -419,7 +495,7 @@ mod tests {
         let mut processor = Processor::init();
         let return_address = 0x201;
         processor.pc = 0x200;
-        processor.stack.push(return_address);
+        processor.stack.push(return_address).unwrap();
 
         // Act
         execute_instruction(&mut processor, 0x00EE);
@@ -431,6 +507,17 @@ mod tests {
         );
         assert!(processor.stack.is_empty(), "Stack not popped!");
     }
+    #[wasm_bindgen_test]
+    fn test_00EE_underflow() {
+        // Arrange
+        let mut processor = Processor::init();
+
+        // Act
+        let result = processor.execute(decode(0x00EE));
+
+        // Assert
+        assert_eq!(result, Err(Fault::StackUnderflow));
+    }
 
     #[wasm_bindgen_test]
     fn test_1NNN() {
@@ -456,12 +543,36 @@ mod tests {
 
         // Assert
         assert_eq!(
-            processor.stack[0],
+            processor.stack.as_slice()[0],
             Memory::ROM_BEGIN_INDEX,
             "PC not added to stack!"
         );
         assert_eq!(processor.pc, nnn, "PC should be {:06X}!", nnn);
     }
+    #[wasm_bindgen_test]
+    fn test_2NNN_overflow() {
+        // Arrange
+        let mut processor = Processor::init();
+        for _ in 0..processor.stack.depth() {
+            processor.stack.push(Memory::ROM_BEGIN_INDEX).unwrap();
+        }
+
+        // Act
+        let result = processor.execute(decode(0x2123));
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(Fault::StackOverflow {
+                depth: processor.stack.depth()
+            })
+        );
+        assert_eq!(
+            processor.pc,
+            Memory::ROM_BEGIN_INDEX,
+            "PC should not change!"
+        );
+    }
 
     #[wasm_bindgen_test]
     fn test_3XNN() {
@@ -693,6 +804,21 @@ mod tests {
         );
     }
     #[wasm_bindgen_test]
+    fn test_8XY6_VF_destination() {
+        // Arrange
+        let mut processor = Processor::init_compat(Compatibility::New);
+        processor.v[0xF] = 0x23;
+
+        // Act
+        execute_instruction(&mut processor, 0x8F06);
+
+        // Assert
+        assert_eq!(
+            processor.v[0xF], 0x1,
+            "The flag should win over the result!"
+        );
+    }
+    #[wasm_bindgen_test]
     fn test_8XY6_new() {
         // Arrange
         let mut processor = Processor::init_compat(Compatibility::New);
@@ -777,6 +903,21 @@ mod tests {
         );
     }
     #[wasm_bindgen_test]
+    fn test_8XYE_VF_destination() {
+        // Arrange
+        let mut processor = Processor::init_compat(Compatibility::New);
+        processor.v[0xF] = 0x40;
+
+        // Act
+        execute_instruction(&mut processor, 0x8F0E);
+
+        // Assert
+        assert_eq!(
+            processor.v[0xF], 0x0,
+            "The flag should win over the result!"
+        );
+    }
+    #[wasm_bindgen_test]
     fn test_8XYE_new() {
         // Arrange
         let mut processor = Processor::init_compat(Compatibility::New);
@@ -863,6 +1004,8 @@ mod tests {
     fn test_CXNN() {
         // Arrange
         let mut processor = Processor::init();
+        // Without a fixed seed the masked random byte is 0 one time in 8
+        processor.seed(1);
         let x = 0x1;
         let nn = 0x23;
         let old_vx = processor.v[x as usize];
@@ -873,6 +1016,23 @@ mod tests {
         // Assert
         assert_ne!(old_vx, processor.v[x as usize]);
     }
+    #[wasm_bindgen_test]
+    fn test_CXNN_seeded() {
+        // Arrange
+        let mut first = Processor::init();
+        let mut second = Processor::init();
+        first.seed(0x1234);
+        second.seed(0x1234);
+
+        // Act
+        for _ in 0..10 {
+            execute_instruction(&mut first, 0xC1FF);
+            execute_instruction(&mut second, 0xC1FF);
+        }
+
+        // Assert
+        assert_eq!(first.v[0x1], second.v[0x1]);
+    }
 
     #[wasm_bindgen_test]
     fn test_DXYN_no_flip() {
@@ -888,20 +1048,54 @@ mod tests {
         processor.v[x as usize] = sprite_x;
         processor.v[y as usize] = sprite_y;
         processor.i = 0x200;
-        processor.memory.data[processor.i as usize] = 0b01000001;
+        processor.memory.write(processor.i, 0b01000001);
 
         // Act
         execute_instruction(&mut processor, 0xD000 | (x << 8) | (y << 4) | n);
 
         // Assert
         assert_eq!(
-            processor.gfx[gfx_start..gfx_start + 8],
+            processor.gfx.to_bytes()[gfx_start..gfx_start + 8],
             [0, 1, 0, 0, 0, 0, 0, 1],
             "processor.gfx set incorrectly!"
         );
         assert_eq!(processor.v[0xF], 0x0, "v[0xF] should be 0x0");
     }
     #[wasm_bindgen_test]
+    fn test_DXYN_records_sprite() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.i = 0x300;
+
+        // Act
+        execute_instruction(&mut processor, 0xD015);
+
+        // Assert
+        assert_eq!(processor.drawn_sprites[0x300], Some(0x5));
+        assert_eq!(processor.drawn_sprites[0x200], None);
+    }
+    #[wasm_bindgen_test]
+    fn test_DXYN_clips_right_edge() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.i = 0x300;
+        processor.memory.write(0x300, 0xFF);
+        processor.v[0x0] = 60;
+        processor.v[0x1] = 0;
+
+        // Act
+        execute_instruction(&mut processor, 0xD011);
+
+        // Assert
+        let gfx = processor.gfx.to_bytes();
+        assert_eq!(gfx[60..64], [1, 1, 1, 1]);
+        assert_eq!(
+            gfx[64..68],
+            [0, 0, 0, 0],
+            "Pixels past the edge shouldn't wrap to the next row!"
+        );
+    }
+    #[wasm_bindgen_test]
     fn test_DXYN_flip() {
         // Arrange
         let mut processor = Processor::init();
@@ -915,15 +1109,15 @@ mod tests {
         processor.v[x as usize] = sprite_x;
         processor.v[y as usize] = sprite_y;
         processor.i = 0x200;
-        processor.memory.data[processor.i as usize] = 0b01000001;
-        processor.gfx = array_init(|_| 1);
+        processor.memory.write(processor.i, 0b01000001);
+        processor.gfx = Framebuffer::from_bytes(&[1; Screen::WIDTH * Screen::HEIGHT]);
 
         // Act
         execute_instruction(&mut processor, 0xD000 | (x << 8) | (y << 4) | n);
 
         // Assert
         assert_eq!(
-            processor.gfx[gfx_start..gfx_start + 8],
+            processor.gfx.to_bytes()[gfx_start..gfx_start + 8],
             [1, 0, 1, 1, 1, 1, 1, 0],
             "processor.gfx set incorrectly!"
         );
@@ -936,7 +1130,7 @@ mod tests {
         let mut processor = Processor::init();
         let x = 0x1;
         processor.v[x as usize] = 0x1;
-        keypad::INSTANCE.lock().unwrap().set_key(0x1);
+        processor.keypad.set_key(0x1);
 
         // Act
         execute_instruction(&mut processor, 0xE09E | (x << 8));
@@ -951,7 +1145,7 @@ mod tests {
         let mut processor = Processor::init();
         let x = 0x1;
         processor.v[x as usize] = 0x1;
-        keypad::INSTANCE.lock().unwrap().set_key(0x2);
+        processor.keypad.set_key(0x2);
 
         // Act
         execute_instruction(&mut processor, 0xE0A1 | (x << 8));
@@ -965,7 +1159,7 @@ mod tests {
         let mut processor = Processor::init();
         let x = 0x1;
         processor.v[x as usize] = 0x1;
-        keypad::INSTANCE.lock().unwrap().unset_key();
+        processor.keypad.unset_key();
 
         // Act
         execute_instruction(&mut processor, 0xE0A1 | (x << 8));
@@ -993,16 +1187,13 @@ mod tests {
         // Arrange
         let mut processor = Processor::init();
         let x = 0x1;
-        keypad::INSTANCE.lock().unwrap().set_key(0x1);
+        processor.keypad.set_key(0x1);
 
         // Act
         execute_instruction(&mut processor, 0xF00A | (x << 8));
 
         // Assert
-        assert_eq!(
-            processor.v[x as usize],
-            keypad::INSTANCE.lock().unwrap().get_current_key()
-        );
+        assert_eq!(processor.v[x as usize], processor.keypad.get_current_key());
     }
 
     #[wasm_bindgen_test]
@@ -1077,6 +1268,18 @@ mod tests {
         // Assert
         assert_eq!(processor.i, Memory::FONT_BEGIN_INDEX + 4 * 5);
     }
+    #[wasm_bindgen_test]
+    fn test_FX29_low_nibble() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.v[0x1] = 0x3A;
+
+        // Act
+        execute_instruction(&mut processor, 0xF129);
+
+        // Assert
+        assert_eq!(processor.i, Memory::FONT_BEGIN_INDEX + 0xA * 5);
+    }
 
     #[wasm_bindgen_test]
     fn test_FX33() {
@@ -1089,9 +1292,9 @@ mod tests {
         execute_instruction(&mut processor, 0xF033 | (x << 8));
 
         // Assert
-        assert_eq!(processor.memory.data[processor.i as usize], 2);
-        assert_eq!(processor.memory.data[processor.i as usize + 1], 5);
-        assert_eq!(processor.memory.data[processor.i as usize + 2], 4);
+        assert_eq!(processor.memory.data()[processor.i as usize], 2);
+        assert_eq!(processor.memory.data()[processor.i as usize + 1], 5);
+        assert_eq!(processor.memory.data()[processor.i as usize + 2], 4);
     }
 
     #[wasm_bindgen_test]
@@ -1110,10 +1313,11 @@ mod tests {
         // Assert
         for i in 0..=x as usize {
             assert_eq!(
-                processor.memory.data[(processor.i - x) as usize + i],
+                processor.memory.data()[Memory::ROM_BEGIN_INDEX as usize + i],
                 processor.v[i]
             );
         }
+        assert_eq!(processor.i, Memory::ROM_BEGIN_INDEX + x + 1);
     }
     #[wasm_bindgen_test]
     fn test_FX55_new() {
@@ -1131,7 +1335,7 @@ mod tests {
         // Assert
         for i in 0..=x as usize {
             assert_eq!(
-                processor.memory.data[processor.i as usize + i],
+                processor.memory.data()[processor.i as usize + i],
                 processor.v[i]
             );
         }
@@ -1144,7 +1348,7 @@ mod tests {
         let x = 0x1_u16;
         processor.i = Memory::ROM_BEGIN_INDEX;
         for i in 0..=x as usize {
-            processor.memory.data[processor.i as usize + i] = i as u8;
+            processor.memory.write(processor.i + i as u16, i as u8);
         }
 
         // Act
@@ -1154,9 +1358,10 @@ mod tests {
         for i in 0..=x as usize {
             assert_eq!(
                 processor.v[i],
-                processor.memory.data[(processor.i) as usize + i]
+                processor.memory.data()[Memory::ROM_BEGIN_INDEX as usize + i]
             );
         }
+        assert_eq!(processor.i, Memory::ROM_BEGIN_INDEX + x + 1);
     }
     #[wasm_bindgen_test]
     fn test_FX65_new() {
@@ -1165,7 +1370,7 @@ mod tests {
         let x = 0x1_u16;
         processor.i = Memory::ROM_BEGIN_INDEX;
         for i in 0..=x as usize {
-            processor.memory.data[processor.i as usize + i] = i as u8;
+            processor.memory.write(processor.i + i as u16, i as u8);
         }
 
         // Act
@@ -1175,8 +1380,72 @@ mod tests {
         for i in 0..=x as usize {
             assert_eq!(
                 processor.v[i],
-                processor.memory.data[processor.i as usize + i]
+                processor.memory.data()[processor.i as usize + i]
             );
         }
     }
+
+    #[wasm_bindgen_test]
+    fn test_DXYN_past_end() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.i = 0xFFE;
+
+        // Act
+        let result = processor.execute(decode(0xD015));
+
+        // Assert
+        assert_eq!(result, Err(Fault::MemoryOutOfBounds { address: 0x1000 }));
+    }
+    #[wasm_bindgen_test]
+    fn test_FX33_past_end() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.i = 0xFFE;
+
+        // Act
+        let result = processor.execute(decode(0xF033));
+
+        // Assert
+        assert_eq!(result, Err(Fault::MemoryOutOfBounds { address: 0x1000 }));
+        assert_eq!(
+            processor.memory.data()[0xFFE],
+            0,
+            "Memory should be left untouched!"
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_FX55_past_end() {
+        // Arrange
+        let mut processor = Processor::init_compat(Compatibility::Original);
+        processor.i = 0xFFF;
+        processor.v[0] = 0xAB;
+
+        // Act
+        let result = processor.execute(decode(0xF155));
+
+        // Assert
+        assert_eq!(result, Err(Fault::MemoryOutOfBounds { address: 0x1000 }));
+        assert_eq!(
+            processor.memory.data()[0xFFF],
+            0,
+            "Memory should be left untouched!"
+        );
+        assert_eq!(processor.i, 0xFFF);
+    }
+    #[wasm_bindgen_test]
+    fn test_FX65_past_end() {
+        // Arrange
+        let mut processor = Processor::init();
+        processor.i = 0xFFF;
+        processor.v[0] = 0xFF;
+        // I wraps around 16 bits but not 12
+        execute_instruction(&mut processor, 0xF01E);
+
+        // Act
+        let result = processor.execute(decode(0xF065));
+
+        // Assert
+        assert_eq!(result, Err(Fault::MemoryOutOfBounds { address: 0x10FE }));
+    }
 }
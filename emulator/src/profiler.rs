@@ -0,0 +1,258 @@
+use crate::components::memory::Memory;
+use crate::components::processor::Compatibility;
+use crate::instruction::{decode, Instruction};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+/** What an address was used for while profiling */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coverage {
+    Unused = 0,
+    /** Executed as an instruction */
+    Code = 1,
+    /** Read as data by DXYN or FX65 */
+    Data = 2,
+    /** Both executed and read as data, usually self-modifying code or a disassembly mistake */
+    Both = 3,
+}
+
+/** Counts executions per address and opcode pattern, and which addresses get read as data */
+#[derive(Debug)]
+pub struct Profiler {
+    executions: Vec<u64>,
+    data_reads: Vec<u64>,
+    patterns: BTreeMap<&'static str, u64>,
+    total: u64,
+}
+impl Profiler {
+    pub fn init() -> Profiler {
+        Profiler {
+            executions: vec![0; Memory::SIZE],
+            data_reads: vec![0; Memory::SIZE],
+            patterns: BTreeMap::new(),
+            total: 0,
+        }
+    }
+
+    /** Records an instruction about to execute at `pc`, with `i` being the index register before it runs */
+    pub fn record(&mut self, pc: u16, instruction: &Instruction, i: u16) {
+        self.executions[pc as usize] += 1;
+        *self.patterns.entry(instruction.pattern()).or_default() += 1;
+        self.total += 1;
+
+        let (reads, _) = data_accesses(instruction, i);
+        for reads in &mut self.data_reads[reads] {
+            *reads += 1;
+        }
+    }
+
+    pub fn executions(&self, address: u16) -> u64 {
+        self.executions[address as usize]
+    }
+    pub fn data_reads(&self, address: u16) -> u64 {
+        self.data_reads[address as usize]
+    }
+    /** Executions of every opcode pattern which ran at least once, e.g. `DXYN` */
+    pub fn patterns(&self) -> &BTreeMap<&'static str, u64> {
+        &self.patterns
+    }
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /** The `count` most executed addresses, most executed first */
+    pub fn hotspots(&self, count: usize) -> Vec<(u16, u64)> {
+        let mut hotspots: Vec<(u16, u64)> = self
+            .executions
+            .iter()
+            .enumerate()
+            .filter(|(_, &executions)| executions > 0)
+            .map(|(address, &executions)| (address as u16, executions))
+            .collect();
+        hotspots.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hotspots.truncate(count);
+
+        hotspots
+    }
+    /** Use of every address, an executed instruction covers both of its bytes */
+    pub fn coverage(&self) -> Vec<Coverage> {
+        (0..Memory::SIZE)
+            .map(|address| {
+                let code =
+                    self.executions[address] > 0 || address > 0 && self.executions[address - 1] > 0;
+                let data = self.data_reads[address] > 0;
+                match (code, data) {
+                    (false, false) => Coverage::Unused,
+                    (true, false) => Coverage::Code,
+                    (false, true) => Coverage::Data,
+                    (true, true) => Coverage::Both,
+                }
+            })
+            .collect()
+    }
+
+    /** Hotspots with their share of all executions and disassembly, followed by counts per opcode pattern */
+    pub fn report(&self, memory: &[u8], compatibility: Compatibility, count: usize) -> String {
+        let mut report = String::new();
+        let share = |executions: u64| executions as f64 * 100.0 / self.total.max(1) as f64;
+
+        writeln!(report, "{} instructions executed", self.total).unwrap();
+        writeln!(report, "\nHotspots:").unwrap();
+        for (address, executions) in self.hotspots(count) {
+            let opcode = (memory[address as usize] as u16) << 0x8
+                | *memory.get(address as usize + 1).unwrap_or(&0) as u16;
+            writeln!(
+                report,
+                "{:#06X}  {:>12}  {:>6.2}%  {}",
+                address,
+                executions,
+                share(executions),
+                decode(opcode).mnemonic(compatibility)
+            )
+            .unwrap();
+        }
+
+        let mut patterns: Vec<_> = self.patterns.iter().collect();
+        patterns.sort_by(|a, b| b.1.cmp(a.1));
+        writeln!(report, "\nOpcodes:").unwrap();
+        for (pattern, executions) in patterns {
+            writeln!(
+                report,
+                "{}  {:>12}  {:>6.2}%",
+                pattern,
+                executions,
+                share(*executions)
+            )
+            .unwrap();
+        }
+
+        report
+    }
+}
+
+/** Addresses the instruction reads and writes as data, given the index register before it runs */
+pub fn data_accesses(instruction: &Instruction, i: u16) -> (Range<usize>, Range<usize>) {
+    let range = |length: usize| {
+        let begin = (i as usize).min(Memory::SIZE);
+        begin..(begin + length).min(Memory::SIZE)
+    };
+
+    match *instruction {
+        Instruction::Draw { n, .. } => (range(n as usize), 0..0),
+        Instruction::LoadRegisters { x } => (range(x as usize + 1), 0..0),
+        Instruction::StoreBcd { .. } => (0..0, range(3)),
+        Instruction::StoreRegisters { x } => (0..0, range(x as usize + 1)),
+        _ => (0..0, 0..0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coverage, Profiler};
+    use crate::components::processor::Processor;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn profiled_processor(program: &[u8]) -> Processor {
+        let mut processor = Processor::init();
+        processor.memory.write_bytes(0x200, program).unwrap();
+        processor.profiler = Some(Profiler::init());
+
+        processor
+    }
+
+    #[wasm_bindgen_test]
+    fn test_record() {
+        // Arrange
+        // 0x200: I = 0x300, 0x202: draw 3 bytes at I, 0x204: jump to 0x202
+        let mut processor = profiled_processor(&[0xA3, 0x00, 0xD0, 0x13, 0x12, 0x02]);
+
+        // Act
+        processor.run(5);
+
+        // Assert
+        let profiler = processor.profiler.unwrap();
+        assert_eq!(profiler.executions(0x200), 1);
+        assert_eq!(profiler.executions(0x202), 2);
+        assert_eq!(profiler.executions(0x204), 2);
+        assert_eq!(profiler.data_reads(0x300), 2);
+        assert_eq!(profiler.data_reads(0x302), 2);
+        assert_eq!(profiler.data_reads(0x303), 0);
+        assert_eq!(profiler.patterns()["DXYN"], 2);
+        assert_eq!(profiler.total(), 5);
+    }
+    #[wasm_bindgen_test]
+    fn test_record_uses_index_before_execution() {
+        // Arrange
+        // 0x200: I = 0x300, 0x202: load V0 and V1 from I, moving I on original systems
+        let mut processor = profiled_processor(&[0xA3, 0x00, 0xF1, 0x65]);
+
+        // Act
+        processor.run(2);
+
+        // Assert
+        let profiler = processor.profiler.unwrap();
+        assert_eq!(profiler.data_reads(0x300), 1);
+        assert_eq!(profiler.data_reads(0x301), 1);
+        assert_eq!(profiler.data_reads(0x302), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hotspots() {
+        // Arrange
+        // 0x200: V0 += 1, 0x202: jump to 0x200
+        let mut processor = profiled_processor(&[0x70, 0x01, 0x12, 0x00]);
+        processor.run(5);
+
+        // Act
+        let result = processor.profiler.unwrap().hotspots(1);
+
+        // Assert
+        assert_eq!(result, [(0x200, 3)]);
+    }
+    #[wasm_bindgen_test]
+    fn test_coverage() {
+        // Arrange
+        // 0x200: I = 0x300, 0x202: draw 1 byte at I
+        let mut processor = profiled_processor(&[0xA3, 0x00, 0xD0, 0x11]);
+        processor.run(2);
+
+        // Act
+        let result = processor.profiler.unwrap().coverage();
+
+        // Assert
+        assert_eq!(result[0x1FF], Coverage::Unused);
+        assert_eq!(result[0x200..0x204], [Coverage::Code; 4]);
+        assert_eq!(result[0x204], Coverage::Unused);
+        assert_eq!(result[0x300], Coverage::Data);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_report() {
+        // Arrange
+        // 0x200: V0 += 1, 0x202: jump to 0x200
+        let mut processor = profiled_processor(&[0x70, 0x01, 0x12, 0x00]);
+        processor.run(4);
+
+        // Act
+        let result = processor.profiler.unwrap().report(
+            processor.memory.data(),
+            processor.compatibility,
+            10,
+        );
+
+        // Assert
+        assert_eq!(
+            result,
+            "4 instructions executed\n\
+             \n\
+             Hotspots:\n\
+             0x0200             2   50.00%  ADD V0, 0x01\n\
+             0x0202             2   50.00%  JP 0x200\n\
+             \n\
+             Opcodes:\n\
+             1NNN             2   50.00%\n\
+             7XNN             2   50.00%\n"
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use crate::components::framebuffer::Framebuffer;
+use crate::components::memory::Memory;
+use crate::components::processor::{Compatibility, Processor};
+use crate::components::screen::Screen;
+use crate::components::stack::Stack;
+use crate::fault::Fault;
+use crate::instruction::decode;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+/**
+ * Everything an opcode can read or change, with its own straightforward implementation of each opcode
+ * to check `opcodes.rs` against. Only shares the fault types with the emulator, not any code.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Model {
+    pc: u16,
+    i: u16,
+    v: [u8; 16],
+    stack: Vec<u16>,
+    delay_timer: u8,
+    sound_timer: u8,
+    key: Option<u8>,
+    memory: Vec<u8>,
+    gfx: Vec<u8>,
+}
+impl Model {
+    fn capture(processor: &Processor) -> Model {
+        Model {
+            pc: processor.pc,
+            i: processor.i,
+            v: processor.v,
+            stack: processor.stack.as_slice().to_vec(),
+            delay_timer: processor.delay_timer,
+            sound_timer: processor.sound_timer,
+            key: processor
+                .keypad
+                .is_key_pressed()
+                .then(|| processor.keypad.get_current_key()),
+            memory: processor.memory.data().to_vec(),
+            gfx: processor.gfx.to_bytes(),
+        }
+    }
+    fn processor(&self, compatibility: Compatibility) -> Processor {
+        let mut processor = Processor::init_compat(compatibility);
+        processor.pc = self.pc;
+        processor.i = self.i;
+        processor.v = self.v;
+        for &address in &self.stack {
+            processor.stack.push(address).unwrap();
+        }
+        processor.delay_timer = self.delay_timer;
+        processor.sound_timer = self.sound_timer;
+        if let Some(key) = self.key {
+            processor.keypad.set_key(key);
+        }
+        processor.memory.load_rom(&self.memory, 0x0).unwrap();
+        processor.gfx = Framebuffer::from_bytes(&self.gfx);
+
+        processor
+    }
+
+    /** Executes `opcode` with PC already pointing past it, like the processor does */
+    fn execute(&mut self, opcode: u16, compatibility: Compatibility) -> Result<(), Fault> {
+        let [a, x, y, n] = [12, 8, 4, 0].map(|shift| (opcode >> shift) as usize & 0xF);
+        let nn = opcode as u8;
+        let nnn = opcode & 0xFFF;
+        let original = compatibility == Compatibility::Original;
+        let (vx, vy) = (self.v[x], self.v[y]);
+
+        match (a, y, n) {
+            (0x0, 0xE, 0x0) if x == 0 => self.gfx.fill(0),
+            (0x0, 0xE, 0xE) if x == 0 => self.pc = self.stack.pop().ok_or(Fault::StackUnderflow)?,
+            (0x1, ..) => self.pc = nnn,
+            (0x2, ..) => {
+                if self.stack.len() == Stack::DEFAULT_DEPTH {
+                    return Err(Fault::StackOverflow {
+                        depth: Stack::DEFAULT_DEPTH,
+                    });
+                }
+                self.stack.push(self.pc);
+                self.pc = nnn;
+            }
+            (0x3, ..) => self.skip_if(vx == nn),
+            (0x4, ..) => self.skip_if(vx != nn),
+            (0x5, _, 0x0) => self.skip_if(vx == vy),
+            (0x6, ..) => self.v[x] = nn,
+            (0x7, ..) => self.v[x] = vx.wrapping_add(nn),
+            (0x8, _, 0x0) => self.v[x] = vy,
+            (0x8, _, 0x1) => self.v[x] = vx | vy,
+            (0x8, _, 0x2) => self.v[x] = vx & vy,
+            (0x8, _, 0x3) => self.v[x] = vx ^ vy,
+            (0x8, _, 0x4) => {
+                let sum = vx as u16 + vy as u16;
+                self.set_with_flag(x, (sum % 0x100) as u8, sum > 0xFF);
+            }
+            (0x8, _, 0x5) => self.set_with_flag(x, vx.wrapping_sub(vy), vx >= vy),
+            (0x8, _, 0x7) => self.set_with_flag(x, vy.wrapping_sub(vx), vy >= vx),
+            (0x8, _, 0x6) => {
+                let source = if original { vy } else { vx };
+                self.set_with_flag(x, source / 2, source % 2 == 1);
+            }
+            (0x8, _, 0xE) => {
+                let source = if original { vy } else { vx };
+                self.set_with_flag(x, source.wrapping_mul(2), source >= 0x80);
+            }
+            (0x9, _, 0x0) => self.skip_if(vx != vy),
+            (0xA, ..) => self.i = nnn,
+            (0xB, ..) => self.pc = nnn + if original { self.v[0] } else { vx } as u16,
+            (0xD, ..) => self.draw(vx, vy, n)?,
+            (0xE, 0x9, 0xE) => self.skip_if(self.key == Some(vx)),
+            (0xE, 0xA, 0x1) => self.skip_if(self.key != Some(vx)),
+            (0xF, 0x0, 0x7) => self.v[x] = self.delay_timer,
+            (0xF, 0x0, 0xA) => match self.key {
+                Some(key) => self.v[x] = key,
+                None => self.pc -= 2,
+            },
+            (0xF, 0x1, 0x5) => self.delay_timer = vx,
+            (0xF, 0x1, 0x8) => self.sound_timer = vx,
+            (0xF, 0x1, 0xE) => {
+                self.i = self.i.wrapping_add(vx as u16);
+                if self.i > 0xFFF {
+                    self.v[0xF] = 1;
+                }
+            }
+            (0xF, 0x2, 0x9) => self.i = 0x50 + (vx % 16) as u16 * 5,
+            (0xF, 0x3, 0x3) => {
+                let address = self.index(3)?;
+                self.memory[address] = vx / 100;
+                self.memory[address + 1] = vx / 10 % 10;
+                self.memory[address + 2] = vx % 10;
+            }
+            (0xF, 0x5, 0x5) => {
+                let address = self.index(x + 1)?;
+                for register in 0..=x {
+                    self.memory[address + register] = self.v[register];
+                }
+                if original {
+                    self.i += x as u16 + 1;
+                }
+            }
+            (0xF, 0x6, 0x5) => {
+                let address = self.index(x + 1)?;
+                for register in 0..=x {
+                    self.v[register] = self.memory[address + register];
+                }
+                if original {
+                    self.i += x as u16 + 1;
+                }
+            }
+            _ => return Err(Fault::UnknownOpcode(opcode)),
+        }
+
+        Ok(())
+    }
+
+    fn skip_if(&mut self, condition: bool) {
+        if condition {
+            self.pc += 2;
+        }
+    }
+    /** The flag is written last, so it's what's left when VF is the destination */
+    fn set_with_flag(&mut self, x: usize, value: u8, flag: bool) {
+        self.v[x] = value;
+        self.v[0xF] = flag as u8;
+    }
+    /** Start of `length` bytes at I, all of which have to be in memory */
+    fn index(&self, length: usize) -> Result<usize, Fault> {
+        let start = self.i as usize;
+        match start < Memory::SIZE && start + length <= Memory::SIZE {
+            true => Ok(start),
+            false => Err(Fault::MemoryOutOfBounds {
+                address: start.max(Memory::SIZE),
+            }),
+        }
+    }
+    /** Sprites start at a position wrapped around the screen and are clipped at its edges */
+    fn draw(&mut self, vx: u8, vy: u8, height: usize) -> Result<(), Fault> {
+        let address = self.index(height.max(1))?;
+        let left = vx as usize % Screen::WIDTH;
+        let top = vy as usize % Screen::HEIGHT;
+        let mut collision = false;
+
+        for row in 0..height {
+            let y = top + row;
+            if y >= Screen::HEIGHT {
+                break;
+            }
+            for column in 0..8 {
+                let x = left + column;
+                if x >= Screen::WIDTH {
+                    break;
+                }
+                if self.memory[address + row] & (0x80 >> column) != 0 {
+                    let pixel = &mut self.gfx[y * Screen::WIDTH + x];
+                    collision |= *pixel == 1;
+                    *pixel ^= 1;
+                }
+            }
+        }
+        self.v[0xF] = collision as u8;
+
+        Ok(())
+    }
+}
+
+/** Bytes which hit equal comparisons, carries and sign bits more often than uniformly random ones */
+fn byte() -> impl Strategy<Value = u8> {
+    prop_oneof![any::<u8>(), 0u8..4, select(vec![0x7F, 0x80, 0xFE, 0xFF])]
+}
+fn model() -> impl Strategy<Value = Model> {
+    (
+        (0x101u16..0x7FF).prop_map(|pc| pc * 2),
+        prop_oneof![0u16..0x1000, 0xFF0u16..0x1000, any::<u16>()],
+        prop::array::uniform16(byte()),
+        vec(0x200u16..0x1000, 0..=Stack::DEFAULT_DEPTH),
+        (byte(), byte()),
+        proptest::option::of(0u8..16),
+        vec(any::<u8>(), Memory::SIZE),
+        vec(0u8..=1, Screen::WIDTH * Screen::HEIGHT),
+    )
+        .prop_map(
+            |(pc, i, v, stack, (delay_timer, sound_timer), key, memory, gfx)| Model {
+                pc,
+                i,
+                v,
+                stack,
+                delay_timer,
+                sound_timer,
+                key,
+                memory,
+                gfx,
+            },
+        )
+}
+fn compatibility() -> impl Strategy<Value = Compatibility> {
+    prop_oneof![Just(Compatibility::Original), Just(Compatibility::New)]
+}
+
+/** Opcode from a prefix like 0xF033, with X filled in */
+fn with_register(prefixes: Vec<u16>) -> impl Strategy<Value = u16> {
+    (select(prefixes), 0u16..16).prop_map(|(prefix, x)| prefix | x << 8)
+}
+/** Opcode from a prefix like 0x8005, with X and Y filled in */
+fn with_registers(prefixes: Vec<u16>) -> impl Strategy<Value = u16> {
+    (select(prefixes), 0u16..16, 0u16..16).prop_map(|(prefix, x, y)| prefix | x << 8 | y << 4)
+}
+/** Opcode from a prefix like 0x3000, with X and NN filled in */
+fn with_immediate(prefixes: Vec<u16>) -> impl Strategy<Value = u16> {
+    (select(prefixes), 0u16..16, byte()).prop_map(|(prefix, x, nn)| prefix | x << 8 | nn as u16)
+}
+fn with_address(prefixes: Vec<u16>) -> impl Strategy<Value = u16> {
+    (select(prefixes), 0u16..0x1000).prop_map(|(prefix, nnn)| prefix | nnn)
+}
+
+fn first_difference(actual: &[u8], expected: &[u8]) -> Option<usize> {
+    actual.iter().zip(expected).position(|(a, b)| a != b)
+}
+fn check(model: Model, opcode: u16, compatibility: Compatibility) -> Result<(), TestCaseError> {
+    let mut processor = model.processor(compatibility);
+    let mut expected = model;
+
+    let expected_result = expected.execute(opcode, compatibility);
+    let result = processor.execute(decode(opcode));
+    let actual = Model::capture(&processor);
+
+    let context = format!("{:04X} with {:?} quirks", opcode, compatibility);
+    prop_assert_eq!(result, expected_result, "Result of {}", context);
+    prop_assert_eq!(actual.v, expected.v, "V registers after {}", context);
+    prop_assert_eq!(
+        (actual.pc, actual.i, &actual.stack),
+        (expected.pc, expected.i, &expected.stack),
+        "PC, I and stack after {}",
+        context
+    );
+    prop_assert_eq!(
+        (actual.delay_timer, actual.sound_timer),
+        (expected.delay_timer, expected.sound_timer),
+        "Timers after {}",
+        context
+    );
+    prop_assert_eq!(
+        first_difference(&actual.memory, &expected.memory),
+        None,
+        "First differing memory address after {}",
+        context
+    );
+    prop_assert_eq!(
+        first_difference(&actual.gfx, &expected.gfx),
+        None,
+        "First differing pixel after {}",
+        context
+    );
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn test_arithmetic(
+        model in model(),
+        opcode in with_registers(vec![0x8000, 0x8001, 0x8002, 0x8003, 0x8004, 0x8005, 0x8006, 0x8007, 0x800E]),
+        compatibility in compatibility(),
+    ) {
+        check(model, opcode, compatibility)?;
+    }
+
+    #[test]
+    fn test_immediates_and_skips(
+        model in model(),
+        opcode in prop_oneof![
+            with_immediate(vec![0x3000, 0x4000, 0x6000, 0x7000]),
+            with_registers(vec![0x5000, 0x9000]),
+        ],
+        compatibility in compatibility(),
+    ) {
+        check(model, opcode, compatibility)?;
+    }
+
+    #[test]
+    fn test_flow(
+        model in model(),
+        opcode in prop_oneof![
+            Just(0x00EE),
+            with_address(vec![0x1000, 0x2000, 0xB000]),
+        ],
+        compatibility in compatibility(),
+    ) {
+        check(model, opcode, compatibility)?;
+    }
+
+    #[test]
+    fn test_index_and_memory(
+        model in model(),
+        opcode in prop_oneof![
+            with_address(vec![0xA000]),
+            with_register(vec![0xF01E, 0xF029, 0xF033, 0xF055, 0xF065]),
+        ],
+        compatibility in compatibility(),
+    ) {
+        check(model, opcode, compatibility)?;
+    }
+
+    #[test]
+    fn test_draw(
+        model in model(),
+        opcode in (0u16..16, 0u16..16, 0u16..16).prop_map(|(x, y, n)| 0xD000 | x << 8 | y << 4 | n),
+        compatibility in compatibility(),
+    ) {
+        check(model, opcode, compatibility)?;
+    }
+
+    #[test]
+    fn test_keys_timers_and_screen(
+        model in model(),
+        opcode in prop_oneof![
+            Just(0x00E0),
+            with_register(vec![0xE09E, 0xE0A1, 0xF007, 0xF00A, 0xF015, 0xF018]),
+        ],
+        compatibility in compatibility(),
+    ) {
+        check(model, opcode, compatibility)?;
+    }
+
+    /** Covers the decoder too, every opcode has to either match the model or be unknown to both */
+    #[test]
+    fn test_any_opcode(
+        model in model(),
+        opcode in any::<u16>().prop_filter("CXNN is random", |opcode| opcode >> 12 != 0xC),
+        compatibility in compatibility(),
+    ) {
+        check(model, opcode, compatibility)?;
+    }
+
+    #[test]
+    fn test_random(model in model(), x in 0usize..16, nn in byte()) {
+        let mut processor = model.processor(Compatibility::New);
+
+        processor.execute(decode(0xC000 | (x as u16) << 8 | nn as u16)).unwrap();
+
+        prop_assert_eq!(processor.v[x] & !nn, 0, "Only bits in NN can be set");
+        for register in (0..16).filter(|&register| register != x) {
+            prop_assert_eq!(processor.v[register], model.v[register]);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::components::framebuffer::Framebuffer;
+use crate::components::screen::Screen;
+
+/** Whether the pixel at `x` of a packed row is on */
+fn is_on(row: u64, x: usize) -> bool {
+    row >> (Screen::WIDTH - 1 - x) & 1 != 0
+}
+
+/** Display as one line of text per row, `#` for pixels which are on and `.` for the rest */
+pub fn to_text(framebuffer: &Framebuffer) -> String {
+    let mut text = String::with_capacity((Screen::WIDTH + 1) * Screen::HEIGHT);
+    for &row in framebuffer.rows() {
+        text.extend((0..Screen::WIDTH).map(|x| if is_on(row, x) { '#' } else { '.' }));
+        text.push('\n');
+    }
+
+    text
+}
+
+/** Display with two pixels per character using half blocks, 64x16 characters for the 64x32 display */
+pub fn to_half_blocks(framebuffer: &Framebuffer) -> Vec<String> {
+    framebuffer
+        .rows()
+        .chunks(2)
+        .map(|pair| {
+            let (top, bottom) = (pair[0], pair.get(1).copied().unwrap_or(0));
+            (0..Screen::WIDTH)
+                .map(|x| match (is_on(top, x), is_on(bottom, x)) {
+                    (false, false) => ' ',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (true, true) => '█',
+                })
+                .collect()
+        })
+        .collect()
+}
+/** Display with 2x4 pixels per character using braille patterns, 32x8 characters for the 64x32 display */
+pub fn to_braille(framebuffer: &Framebuffer) -> Vec<String> {
+    // Bit of each dot in a braille pattern, by row and then column
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+    framebuffer
+        .rows()
+        .chunks(4)
+        .map(|rows| {
+            (0..Screen::WIDTH / 2)
+                .map(|column| {
+                    let mut pattern = 0;
+                    for (&row, dots) in rows.iter().zip(&DOTS) {
+                        for (dx, dot) in dots.iter().enumerate() {
+                            if is_on(row, column * 2 + dx) {
+                                pattern |= dot;
+                            }
+                        }
+                    }
+                    char::from_u32(0x2800 + pattern).unwrap_or(' ')
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/** Display as a black and white PNG, each pixel blown up to `scale`x`scale` */
+pub fn to_png(framebuffer: &Framebuffer, scale: usize) -> Vec<u8> {
+    let scale = scale.max(1);
+    let (width, height) = (Screen::WIDTH * scale, Screen::HEIGHT * scale);
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for &row in framebuffer.rows() {
+        let line: Vec<u8> = (0..Screen::WIDTH)
+            .flat_map(|x| std::iter::repeat_n(if is_on(row, x) { 0xFF } else { 0x00 }, scale))
+            .collect();
+        for _ in 0..scale {
+            pixels.extend_from_slice(&line);
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    // Writing to a Vec can't fail and the size always matches
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&pixels).unwrap();
+    writer.finish().unwrap();
+
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_braille, to_half_blocks, to_png, to_text};
+    use crate::components::framebuffer::Framebuffer;
+    use crate::components::screen::Screen;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn corner_framebuffer() -> Framebuffer {
+        let mut framebuffer = Framebuffer::init();
+        framebuffer.set(0, 0, true);
+        framebuffer.set(Screen::WIDTH - 1, Screen::HEIGHT - 1, true);
+
+        framebuffer
+    }
+
+    #[wasm_bindgen_test]
+    fn test_to_text() {
+        // Arrange
+        let framebuffer = corner_framebuffer();
+
+        // Act
+        let result = to_text(&framebuffer);
+
+        // Assert
+        let lines: Vec<_> = result.lines().collect();
+        assert_eq!(lines.len(), Screen::HEIGHT);
+        assert_eq!(lines[0], format!("#{}", ".".repeat(63)));
+        assert_eq!(lines[31], format!("{}#", ".".repeat(63)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_to_half_blocks() {
+        // Arrange
+        let framebuffer = corner_framebuffer();
+
+        // Act
+        let result = to_half_blocks(&framebuffer);
+
+        // Assert
+        assert_eq!(result.len(), Screen::HEIGHT / 2);
+        assert_eq!(result[0], format!("▀{}", " ".repeat(63)));
+        assert_eq!(result[15], format!("{}▄", " ".repeat(63)));
+    }
+    #[wasm_bindgen_test]
+    fn test_to_braille() {
+        // Arrange
+        let framebuffer = corner_framebuffer();
+
+        // Act
+        let result = to_braille(&framebuffer);
+
+        // Assert
+        assert_eq!(result.len(), Screen::HEIGHT / 4);
+        assert_eq!(result[0], format!("⠁{}", "⠀".repeat(31)));
+        assert_eq!(result[7], format!("{}⢀", "⠀".repeat(31)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_to_png() {
+        // Arrange
+        let framebuffer = corner_framebuffer();
+
+        // Act
+        let result = to_png(&framebuffer, 2);
+
+        // Assert
+        let decoder = png::Decoder::new(result.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (128, 64));
+        assert_eq!(pixels[..3], [0xFF, 0xFF, 0x00]);
+        assert_eq!(pixels[128..131], [0xFF, 0xFF, 0x00]);
+        assert_eq!(pixels[pixels.len() - 1], 0xFF);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::components::memory::Memory;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/** Sprite in memory, a height of 0 means a 16x16 SCHIP sprite like `DXY0` draws */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sprite {
+    pub address: u16,
+    pub height: u8,
+}
+impl Sprite {
+    /** Width and height in pixels */
+    pub fn size(&self) -> (usize, usize) {
+        match self.height {
+            0 => (16, 16),
+            height => (8, height as usize),
+        }
+    }
+    /** Number of bytes the sprite takes up in memory */
+    pub fn bytes(&self) -> usize {
+        let (width, height) = self.size();
+        width / 8 * height
+    }
+    fn pixel(&self, memory: &[u8], x: usize, y: usize) -> bool {
+        let (width, _) = self.size();
+        let index = self.address as usize + y * width / 8 + x / 8;
+        memory
+            .get(index)
+            .is_some_and(|byte| byte >> (7 - x % 8) & 0x1 == 1)
+    }
+}
+
+/** `count` sprites of the same height laid out one after another starting at `address` */
+pub fn sprites_in_range(address: u16, count: usize, height: u8) -> Vec<Sprite> {
+    let bytes = Sprite { address, height }.bytes();
+
+    (0..count)
+        .map(|i| address as usize + i * bytes)
+        .take_while(|&address| address < Memory::SIZE)
+        .map(|address| Sprite {
+            address: address as u16,
+            height,
+        })
+        .collect()
+}
+
+/** RGBA image of sprites, each with its address above it */
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteSheet {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+impl SpriteSheet {
+    const BACKGROUND: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+    const SPRITE_BACKGROUND: [u8; 4] = [0x30, 0x30, 0x30, 0xFF];
+    const SPRITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+    const LABEL: [u8; 4] = [0xFF, 0xC0, 0x40, 0xFF];
+
+    const PADDING: usize = 2;
+    const GLYPH_WIDTH: usize = 4;
+    const GLYPH_HEIGHT: usize = 5;
+    /** Three hex digits with a pixel of space between them */
+    const LABEL_WIDTH: usize = 3 * (SpriteSheet::GLYPH_WIDTH + 1) - 1;
+
+    /** Lays out the sprites in a grid with `columns` cells per row */
+    pub fn render(memory: &[u8], sprites: &[Sprite], columns: usize) -> SpriteSheet {
+        let columns = columns.clamp(1, sprites.len().max(1));
+        let rows = sprites.len().div_ceil(columns);
+        let (sprite_width, sprite_height) = sprites.iter().fold((0, 0), |(w, h), sprite| {
+            let (width, height) = sprite.size();
+            (width.max(w), height.max(h))
+        });
+
+        let cell_width = sprite_width.max(SpriteSheet::LABEL_WIDTH) + SpriteSheet::PADDING;
+        let cell_height = SpriteSheet::GLYPH_HEIGHT + 1 + sprite_height + SpriteSheet::PADDING;
+        let mut sheet = SpriteSheet {
+            width: columns * cell_width + SpriteSheet::PADDING,
+            height: rows * cell_height + SpriteSheet::PADDING,
+            pixels: Vec::new(),
+        };
+        sheet.pixels = SpriteSheet::BACKGROUND.repeat(sheet.width * sheet.height);
+
+        for (i, sprite) in sprites.iter().enumerate() {
+            let left = SpriteSheet::PADDING + i % columns * cell_width;
+            let top = SpriteSheet::PADDING + i / columns * cell_height;
+            sheet.draw_label(left, top, sprite.address);
+
+            let top = top + SpriteSheet::GLYPH_HEIGHT + 1;
+            let (width, height) = sprite.size();
+            for y in 0..height {
+                for x in 0..width {
+                    let color = match sprite.pixel(memory, x, y) {
+                        true => SpriteSheet::SPRITE,
+                        false => SpriteSheet::SPRITE_BACKGROUND,
+                    };
+                    sheet.set_pixel(left + x, top + y, color);
+                }
+            }
+        }
+
+        sheet
+    }
+
+    /** Draws the address as three hex digits using the built-in font */
+    fn draw_label(&mut self, left: usize, top: usize, address: u16) {
+        for digit in 0..3 {
+            let value = (address >> ((2 - digit) * 4) & 0xF) as usize;
+            let glyph = &Memory::FONT_SET[value * 5..value * 5 + SpriteSheet::GLYPH_HEIGHT];
+            let glyph_left = left + digit * (SpriteSheet::GLYPH_WIDTH + 1);
+
+            for (y, row) in glyph.iter().enumerate() {
+                for x in 0..SpriteSheet::GLYPH_WIDTH {
+                    if row >> (7 - x) & 0x1 == 1 {
+                        self.set_pixel(glyph_left + x, top + y, SpriteSheet::LABEL);
+                    }
+                }
+            }
+        }
+    }
+    fn set_pixel(&mut self, x: usize, y: usize, color: [u8; 4]) {
+        let index = (y * self.width + x) * 4;
+        self.pixels[index..index + 4].copy_from_slice(&color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sprites_in_range, Sprite, SpriteSheet};
+    use crate::components::memory::Memory;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_sprites_in_range() {
+        // Arrange
+        let address = 0xFF0;
+
+        // Act
+        let chip8 = sprites_in_range(address, 4, 5);
+        let schip = sprites_in_range(address, 2, 0);
+
+        // Assert
+        let addresses: Vec<_> = chip8.iter().map(|sprite| sprite.address).collect();
+        assert_eq!(addresses, [0xFF0, 0xFF5, 0xFFA, 0xFFF]);
+        assert_eq!(
+            schip.len(),
+            1,
+            "Sprites past the end of memory should be skipped!"
+        );
+        assert_eq!(schip[0].bytes(), 32);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_render() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.load_fonts();
+        // The font's 0 and 1
+        let sprites = sprites_in_range(Memory::FONT_BEGIN_INDEX, 2, 5);
+
+        // Act
+        let sheet = SpriteSheet::render(memory.data(), &sprites, 2);
+
+        // Assert
+        let pixel = |x: usize, y: usize| {
+            let index = (y * sheet.width + x) * 4;
+            &sheet.pixels[index..index + 4]
+        };
+        // Two cells of 14 + 2 pixels plus the padding on the left
+        assert_eq!((sheet.width, sheet.height), (2 + 2 * 16, 2 + 13));
+        assert_eq!(sheet.pixels.len(), sheet.width * sheet.height * 4);
+        // Top left pixel of the "0" in label "050"
+        assert_eq!(pixel(2, 2), SpriteSheet::LABEL);
+        // Top row of the font's 0 is 0xF0
+        assert_eq!(pixel(2, 8), SpriteSheet::SPRITE);
+        assert_eq!(pixel(6, 8), SpriteSheet::SPRITE_BACKGROUND);
+        // Top row of the font's 1 is 0x20
+        assert_eq!(pixel(18, 8), SpriteSheet::SPRITE_BACKGROUND);
+        assert_eq!(pixel(20, 8), SpriteSheet::SPRITE);
+    }
+    #[wasm_bindgen_test]
+    fn test_render_schip() {
+        // Arrange
+        let mut memory = Memory::init();
+        memory.write(0x300, 0x80);
+        memory.write(0x301, 0x01);
+        let sprite = Sprite {
+            address: 0x300,
+            height: 0,
+        };
+
+        // Act
+        let sheet = SpriteSheet::render(memory.data(), &[sprite], 1);
+
+        // Assert
+        let pixel = |x: usize, y: usize| {
+            let index = (y * sheet.width + x) * 4;
+            &sheet.pixels[index..index + 4]
+        };
+        assert_eq!(sheet.width, 2 + 16 + 2);
+        assert_eq!(pixel(2, 8), SpriteSheet::SPRITE);
+        assert_eq!(pixel(17, 8), SpriteSheet::SPRITE);
+        assert_eq!(pixel(3, 8), SpriteSheet::SPRITE_BACKGROUND);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::components::processor::{Compatibility, Processor};
+use crate::instruction::{decode, Instruction};
+use std::fmt;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/** Snapshot of the processor for debugger panels, fields are copies so it doesn't borrow the emulator */
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuState {
+    pub pc: u16,
+    pub i: u16,
+    pub v: Vec<u8>,
+    /** Return addresses, from the oldest call to the newest */
+    pub stack: Vec<u16>,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    /** Number of cycles executed since the last reset */
+    pub cycles: u64,
+    pub compatibility: Compatibility,
+    /** Set when a fatal fault stopped the processor */
+    pub halted: bool,
+    pub fault: Option<String>,
+    /** Set when the current instruction is FX0A and no key is pressed */
+    pub waiting_for_key: bool,
+    /** Raw opcode at PC */
+    pub opcode: u16,
+    /** Mnemonic of the instruction at PC */
+    pub instruction: String,
+}
+impl CpuState {
+    pub fn capture(processor: &Processor) -> CpuState {
+        let opcode = processor.fetch();
+        let instruction = decode(opcode);
+
+        CpuState {
+            pc: processor.pc,
+            i: processor.i,
+            v: processor.v.to_vec(),
+            stack: processor.stack.as_slice().to_vec(),
+            delay_timer: processor.delay_timer,
+            sound_timer: processor.sound_timer,
+            cycles: processor.cycles,
+            compatibility: processor.compatibility,
+            halted: processor.fault.is_some(),
+            fault: processor.fault.map(|fault| fault.to_string()),
+            waiting_for_key: matches!(instruction, Instruction::WaitForKey { .. })
+                && !processor.keypad.is_key_pressed(),
+            opcode,
+            instruction: instruction.mnemonic(processor.compatibility),
+        }
+    }
+}
+impl fmt::Display for CpuState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let stack: Vec<_> = self
+            .stack
+            .iter()
+            .map(|address| format!("{:#06X}", address))
+            .collect();
+
+        writeln!(
+            f,
+            "PC: {:#06X}  I: {:#06X}  DT: {}  ST: {}  cycles: {}",
+            self.pc, self.i, self.delay_timer, self.sound_timer, self.cycles
+        )?;
+        writeln!(f, "V0-V7: {}", hex(&self.v[..8]))?;
+        writeln!(f, "V8-VF: {}", hex(&self.v[8..]))?;
+        writeln!(f, "Stack: [{}]", stack.join(", "))?;
+        writeln!(f, "Next: {:04X}  {}", self.opcode, self.instruction)?;
+        if let Some(fault) = &self.fault {
+            writeln!(f, "Fault: {}", fault)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CpuState;
+    use crate::components::processor::{Compatibility, Processor};
+    use crate::fault::Fault;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_capture() {
+        // Arrange
+        let mut processor = Processor::init_newer();
+        // 0x200: call 0x300, 0x300: VA = 0x02
+        processor.memory.write_bytes(0x200, &[0x23, 0x00]).unwrap();
+        processor.memory.write_bytes(0x300, &[0x6A, 0x02]).unwrap();
+        processor.cycle();
+
+        // Act
+        let result = CpuState::capture(&processor);
+
+        // Assert
+        assert_eq!(result.pc, 0x300);
+        assert_eq!(result.stack, [0x202]);
+        assert_eq!(result.v.len(), 16);
+        assert_eq!(result.cycles, 1);
+        assert_eq!(result.compatibility, Compatibility::New);
+        assert_eq!(result.opcode, 0x6A02);
+        assert_eq!(result.instruction, "LD VA, 0x02");
+        assert!(!result.halted);
+        assert!(!result.waiting_for_key);
+    }
+    #[wasm_bindgen_test]
+    fn test_capture_waiting_for_key() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: wait for a key and store it in V0
+        processor.memory.write_bytes(0x200, &[0xF0, 0x0A]).unwrap();
+        processor.cycle();
+
+        // Act
+        let waiting = CpuState::capture(&processor).waiting_for_key;
+        processor.keypad.set_key(0x5);
+        let pressed = CpuState::capture(&processor).waiting_for_key;
+
+        // Assert
+        assert!(waiting);
+        assert!(!pressed);
+    }
+    #[wasm_bindgen_test]
+    fn test_capture_halted() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: return without a call
+        processor.memory.write_bytes(0x200, &[0x00, 0xEE]).unwrap();
+        processor.cycle();
+
+        // Act
+        let result = CpuState::capture(&processor);
+
+        // Assert
+        assert!(result.halted);
+        assert_eq!(result.fault, Some(Fault::StackUnderflow.to_string()));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_display() {
+        // Arrange
+        let mut processor = Processor::init();
+        // 0x200: return without a call
+        processor.memory.write_bytes(0x200, &[0x00, 0xEE]).unwrap();
+        processor.v[0xF] = 0x1;
+        processor.i = 0x2A4;
+        processor.cycle();
+
+        // Act
+        let result = CpuState::capture(&processor).to_string();
+
+        // Assert
+        assert_eq!(
+            result,
+            "PC: 0x0200  I: 0x02A4  DT: 0  ST: 0  cycles: 1\n\
+             V0-V7: 00 00 00 00 00 00 00 00\n\
+             V8-VF: 00 00 00 00 00 00 00 01\n\
+             Stack: []\n\
+             Next: 00EE  RET\n\
+             Fault: Stack underflow (return without a call)!\n"
+        );
+    }
+}
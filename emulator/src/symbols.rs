@@ -0,0 +1,283 @@
+use crate::instruction::Instruction;
+use std::collections::BTreeMap;
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SymbolError {
+    pub line: usize,
+    pub message: String,
+}
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+impl std::error::Error for SymbolError {}
+impl From<SymbolError> for JsValue {
+    fn from(err: SymbolError) -> JsValue {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/** Place in the source code an address was assembled from */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: u32,
+}
+impl fmt::Display for SourceLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/**
+ * Labels and source lines of a ROM, one per line as `<address> <label>` or `<address> <file>:<line>`.
+ * Labels can contain `:`, unless they end in `:<number>` or have a `.` before the last `:`, which make them source lines:
+ *
+ * ```text
+ * # Generated by the assembler
+ * 0x200 main
+ * 0x200 game.8o:12
+ * 0x2A4 draw_player
+ * 0x2B0 draw_player::loop
+ * ```
+ */
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Symbols {
+    labels: BTreeMap<u16, String>,
+    lines: BTreeMap<u16, SourceLine>,
+}
+impl Symbols {
+    pub fn parse(text: &str) -> Result<Symbols, SymbolError> {
+        let mut symbols = Symbols::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| SymbolError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+
+            let (address, name) = line.split_once(char::is_whitespace).ok_or_else(|| {
+                error("Expected `<address> <label>` or `<address> <file>:<line>`")
+            })?;
+            let address = u16::from_str_radix(address.trim_start_matches("0x"), 16)
+                .ok()
+                .filter(|&address| address <= 0xFFF)
+                .ok_or_else(|| error("Invalid address"))?;
+            let name = name.trim();
+
+            match name.rsplit_once(':') {
+                Some((file, line)) if file.contains('.') || line.parse::<u32>().is_ok() => {
+                    let line = line
+                        .parse::<u32>()
+                        .map_err(|_| error("Invalid line number"))?;
+                    symbols.lines.insert(
+                        address,
+                        SourceLine {
+                            file: file.to_string(),
+                            line,
+                        },
+                    );
+                }
+                _ => {
+                    symbols.labels.insert(address, name.to_string());
+                }
+            }
+        }
+
+        Ok(symbols)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.lines.is_empty()
+    }
+
+    /** Nearest label at or before `address`, with the distance from it if there is any, e.g. `draw_player+0x4` */
+    pub fn label(&self, address: u16) -> Option<String> {
+        let (&label_address, label) = self.labels.range(..=address).next_back()?;
+        match address - label_address {
+            0 => Some(label.clone()),
+            offset => Some(format!("{}+{:#X}", label, offset)),
+        }
+    }
+    /** Source line of the nearest address at or before `address` which has one */
+    pub fn source_line(&self, address: u16) -> Option<&SourceLine> {
+        self.lines
+            .range(..=address)
+            .next_back()
+            .map(|(_, line)| line)
+    }
+
+    /** Address of a label or of the first address assembled from a `file:line` */
+    pub fn resolve(&self, name: &str) -> Option<u16> {
+        if let Some((&address, _)) = self.labels.iter().find(|(_, label)| *label == name) {
+            return Some(address);
+        }
+        self.lines
+            .iter()
+            .find(|(_, line)| line.to_string() == name)
+            .map(|(&address, _)| address)
+    }
+
+    /** Address with its label and source line, e.g. `0x02A4 <draw_player> (game.8o:40)` */
+    pub fn describe(&self, address: u16) -> String {
+        let mut description = format!("{:#06X}", address);
+        if let Some(label) = self.label(address) {
+            description += &format!(" <{}>", label);
+        }
+        if let Some(line) = self.source_line(address) {
+            description += &format!(" ({})", line);
+        }
+
+        description
+    }
+    /**
+     * Comment for the instruction at `address`: its label and source line,
+     * and the label of the address it jumps to, calls or points I at
+     */
+    pub fn annotate(&self, address: u16, instruction: &Instruction) -> String {
+        let mut parts = Vec::new();
+        if let Some(label) = self.labels.get(&address) {
+            parts.push(format!("{}:", label));
+        }
+        if let Some(line) = self.lines.get(&address) {
+            parts.push(line.to_string());
+        }
+
+        let target = match *instruction {
+            Instruction::Jump { nnn }
+            | Instruction::Call { nnn }
+            | Instruction::SetIndex { nnn }
+            | Instruction::JumpWithOffset { nnn, .. } => Some(nnn),
+            _ => None,
+        };
+        if let Some(label) = target.and_then(|target| self.label(target)) {
+            parts.push(format!("-> {}", label));
+        }
+
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SourceLine, Symbols};
+    use crate::instruction::decode;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const SYMBOLS: &str = "\
+        # Test symbols\n\
+        0x200 main\n\
+        0x200 game.8o:12\n\
+        0x204 game.8o:13\n\
+        2A4 draw_player\n";
+
+    #[wasm_bindgen_test]
+    fn test_parse() {
+        // Arrange
+        let text = SYMBOLS;
+
+        // Act
+        let result = Symbols::parse(text);
+
+        // Assert
+        let symbols = result.unwrap();
+        assert_eq!(symbols.label(0x200), Some("main".to_string()));
+        assert_eq!(symbols.label(0x2A4), Some("draw_player".to_string()));
+        assert_eq!(
+            symbols.source_line(0x204),
+            Some(&SourceLine {
+                file: "game.8o".to_string(),
+                line: 13
+            })
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_parse_label_with_colons() {
+        // Arrange
+        let text = "0x200 draw_player::loop\n0x202 main:\n0x204 sprites.8o:5\n";
+
+        // Act
+        let result = Symbols::parse(text);
+
+        // Assert
+        let symbols = result.unwrap();
+        assert_eq!(symbols.label(0x200), Some("draw_player::loop".to_string()));
+        assert_eq!(symbols.resolve("main:"), Some(0x202));
+        assert_eq!(symbols.label(0x204), Some("main:+0x2".to_string()));
+    }
+    #[wasm_bindgen_test]
+    fn test_parse_error() {
+        // Arrange
+        let texts = ["main", "0x200 game.8o:twelve", "0x1000 main", "zz main"];
+
+        // Act
+        let results = texts.map(Symbols::parse);
+
+        // Assert
+        for result in results {
+            assert_eq!(result.unwrap_err().line, 1);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_label_offset() {
+        // Arrange
+        let symbols = Symbols::parse(SYMBOLS).unwrap();
+
+        // Act
+        let results = [0x1FE, 0x206, 0x2A8].map(|address| symbols.label(address));
+
+        // Assert
+        assert_eq!(
+            results,
+            [
+                None,
+                Some("main+0x6".to_string()),
+                Some("draw_player+0x4".to_string())
+            ]
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_resolve() {
+        // Arrange
+        let symbols = Symbols::parse(SYMBOLS).unwrap();
+
+        // Act
+        let results = ["draw_player", "game.8o:13", "missing"].map(|name| symbols.resolve(name));
+
+        // Assert
+        assert_eq!(results, [Some(0x2A4), Some(0x204), None]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_describe() {
+        // Arrange
+        let symbols = Symbols::parse(SYMBOLS).unwrap();
+
+        // Act
+        let result = symbols.describe(0x206);
+
+        // Assert
+        assert_eq!(result, "0x0206 <main+0x6> (game.8o:13)");
+    }
+    #[wasm_bindgen_test]
+    fn test_annotate() {
+        // Arrange
+        let symbols = Symbols::parse(SYMBOLS).unwrap();
+
+        // Act
+        let call = symbols.annotate(0x200, &decode(0x22A4));
+        let plain = symbols.annotate(0x202, &decode(0x6A02));
+
+        // Assert
+        assert_eq!(call, "main: game.8o:12 -> draw_player");
+        assert_eq!(plain, "");
+    }
+}
@@ -0,0 +1,331 @@
+use crate::components::processor::Processor;
+use crate::instruction::decode;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/** Registers, I and stack at one point of execution */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterState {
+    pub pc: u16,
+    pub i: u16,
+    pub v: [u8; 16],
+    pub stack: Vec<u16>,
+}
+impl RegisterState {
+    pub fn capture(processor: &Processor) -> RegisterState {
+        RegisterState {
+            pc: processor.pc,
+            i: processor.i,
+            v: processor.v,
+            stack: processor.stack.as_slice().to_vec(),
+        }
+    }
+}
+impl fmt::Display for RegisterState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PC={:#06X} I={:#06X} V=", self.pc, self.i)?;
+        for value in self.v {
+            write!(f, "{:02X}", value)?;
+        }
+        write!(f, " S=[")?;
+        for (i, address) in self.stack.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{:#06X}", address)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/** One executed instruction with the machine state around it */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub cycle: u64,
+    pub pc: u16,
+    pub opcode: u16,
+    pub mnemonic: String,
+    pub before: RegisterState,
+    pub after: RegisterState,
+}
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08} {:#06X}: {:04X} {:<18} {} -> {}",
+            self.cycle, self.pc, self.opcode, self.mnemonic, self.before, self.after
+        )
+    }
+}
+
+/** Decides which instructions get recorded */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceFilter {
+    /** Addresses of recorded instructions */
+    pub addresses: RangeInclusive<u16>,
+    /** Bit N set records opcodes with N as the highest nibble, e.g. bit 0xD for DXYN */
+    pub opcode_classes: u16,
+}
+impl TraceFilter {
+    pub const ALL_CLASSES: u16 = 0xFFFF;
+
+    pub fn matches(&self, pc: u16, opcode: u16) -> bool {
+        self.addresses.contains(&pc) && self.opcode_classes & (1 << (opcode >> 0xC)) != 0
+    }
+}
+impl Default for TraceFilter {
+    fn default() -> TraceFilter {
+        TraceFilter {
+            addresses: 0x000..=0xFFF,
+            opcode_classes: TraceFilter::ALL_CLASSES,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Tracer {
+    pub enabled: bool,
+    pub filter: TraceFilter,
+    /** Maximum number of kept entries, the oldest ones are dropped first */
+    capacity: usize,
+    entries: VecDeque<TraceEntry>,
+}
+impl Tracer {
+    pub const DEFAULT_CAPACITY: usize = 100_000;
+
+    pub fn init() -> Tracer {
+        Tracer {
+            enabled: false,
+            filter: TraceFilter::default(),
+            capacity: Tracer::DEFAULT_CAPACITY,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /** Enables recording, dropping entries from the previous trace */
+    pub fn start(&mut self, capacity: usize) {
+        self.enabled = true;
+        self.capacity = capacity;
+        self.entries.clear();
+    }
+    pub fn stop(&mut self) {
+        self.enabled = false;
+    }
+
+    /** Runs a single cycle, recording it if it passes the filter */
+    pub fn cycle(&mut self, processor: &mut Processor) {
+        if !self.enabled || processor.fault.is_some() {
+            processor.cycle();
+            return;
+        }
+
+        let pc = processor.pc;
+        let opcode = processor.fetch();
+        if !self.filter.matches(pc, opcode) {
+            processor.cycle();
+            return;
+        }
+
+        let before = RegisterState::capture(processor);
+        processor.cycle();
+        if processor.hit_breakpoint.is_some() {
+            return;
+        }
+        let after = RegisterState::capture(processor);
+
+        self.record(TraceEntry {
+            cycle: processor.cycles,
+            pc,
+            opcode,
+            mnemonic: decode(opcode).mnemonic(processor.compatibility),
+            before,
+            after,
+        });
+    }
+    pub fn record(&mut self, entry: TraceEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /** One line per entry, meant for reading and diffing */
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect()
+    }
+    /** One JSON object per line */
+    pub fn to_json_lines(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}\n",
+                    serde_json::to_string(entry).expect("Failed serializing trace entry!")
+                )
+            })
+            .collect()
+    }
+}
+
+/** Parses entries exported by `Tracer::to_json_lines`, e.g. a reference log from another run */
+pub fn from_json_lines(text: &str) -> Result<Vec<TraceEntry>, serde_json::Error> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_json_lines, TraceFilter, Tracer};
+    use crate::components::processor::Processor;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn processor_with_program(program: &[u8]) -> Processor {
+        let mut processor = Processor::init();
+        processor.memory.write_bytes(0x200, program).unwrap();
+
+        processor
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cycle_records_entry() {
+        // Arrange
+        // 0x200: VA = 0x02, 0x202: call 0x300
+        let mut processor = processor_with_program(&[0x6A, 0x02, 0x23, 0x00]);
+        let mut tracer = Tracer::init();
+        tracer.start(Tracer::DEFAULT_CAPACITY);
+
+        // Act
+        tracer.cycle(&mut processor);
+        tracer.cycle(&mut processor);
+
+        // Assert
+        let entries: Vec<_> = tracer.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].cycle, 1);
+        assert_eq!(entries[0].pc, 0x200);
+        assert_eq!(entries[0].opcode, 0x6A02);
+        assert_eq!(entries[0].mnemonic, "LD VA, 0x02");
+        assert_eq!(entries[0].before.v[0xA], 0x00);
+        assert_eq!(entries[0].after.v[0xA], 0x02);
+        assert_eq!(entries[1].after.pc, 0x300);
+        assert_eq!(entries[1].after.stack, vec![0x204]);
+    }
+    #[wasm_bindgen_test]
+    fn test_cycle_disabled() {
+        // Arrange
+        let mut processor = processor_with_program(&[0x6A, 0x02]);
+        let mut tracer = Tracer::init();
+
+        // Act
+        tracer.cycle(&mut processor);
+
+        // Assert
+        assert!(tracer.is_empty());
+        assert_eq!(processor.v[0xA], 0x02, "Cycle should run anyway!");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_filter() {
+        // Arrange
+        let filter = TraceFilter {
+            addresses: 0x200..=0x2FF,
+            opcode_classes: 1 << 0xD,
+        };
+
+        // Act
+        let results = [
+            filter.matches(0x200, 0xD123),
+            filter.matches(0x2FF, 0xD123),
+            filter.matches(0x300, 0xD123),
+            filter.matches(0x200, 0x6123),
+        ];
+
+        // Assert
+        assert_eq!(results, [true, true, false, false]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_capacity() {
+        // Arrange
+        // 0x200: jump to 0x200
+        let mut processor = processor_with_program(&[0x12, 0x00]);
+        let mut tracer = Tracer::init();
+        tracer.start(2);
+
+        // Act
+        for _ in 0..3 {
+            tracer.cycle(&mut processor);
+        }
+
+        // Assert
+        let cycles: Vec<_> = tracer.entries().map(|entry| entry.cycle).collect();
+        assert_eq!(cycles, [2, 3], "Oldest entry should be dropped!");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_export() {
+        // Arrange
+        // 0x200: call 0x300
+        let mut processor = processor_with_program(&[0x23, 0x00]);
+        let mut tracer = Tracer::init();
+        tracer.start(Tracer::DEFAULT_CAPACITY);
+        tracer.cycle(&mut processor);
+
+        // Act
+        let text = tracer.to_text();
+        let json_lines = tracer.to_json_lines();
+
+        // Assert
+        assert_eq!(
+            text,
+            "00000001 0x0200: 2300 CALL 0x300         \
+             PC=0x0200 I=0x0000 V=00000000000000000000000000000000 S=[] -> \
+             PC=0x0300 I=0x0000 V=00000000000000000000000000000000 S=[0x0202]\n"
+        );
+        assert_eq!(
+            json_lines,
+            "{\"cycle\":1,\"pc\":512,\"opcode\":8960,\"mnemonic\":\"CALL 0x300\",\
+             \"before\":{\"pc\":512,\"i\":0,\"v\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"stack\":[]},\
+             \"after\":{\"pc\":768,\"i\":0,\"v\":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],\"stack\":[514]}}\n"
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_from_json_lines() {
+        // Arrange
+        let mut processor = processor_with_program(&[0x6A, 0x02, 0x23, 0x00]);
+        let mut tracer = Tracer::init();
+        tracer.start(Tracer::DEFAULT_CAPACITY);
+        tracer.cycle(&mut processor);
+        tracer.cycle(&mut processor);
+
+        // Act
+        let result = from_json_lines(&tracer.to_json_lines());
+
+        // Assert
+        let expected: Vec<_> = tracer.entries().cloned().collect();
+        assert_eq!(result.unwrap(), expected);
+    }
+}
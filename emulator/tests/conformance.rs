@@ -0,0 +1,137 @@
+//! Runs the test ROMs in `tests/roms` and compares their final framebuffers against `tests/roms/golden.txt`
+#![cfg(not(target_arch = "wasm32"))]
+
+use chip8_emulator::input::InputScript;
+use chip8_emulator::{Compatibility, Emulator};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+struct Case {
+    rom: String,
+    quirks: String,
+    frames: u64,
+    /** Byte written to 0x1FF before running, which preselects a menu entry in the Timendus tests */
+    preset: Option<u8>,
+    /** Input script in `tests/roms` for ROMs which wait for key presses */
+    inputs: Option<String>,
+    hash: Option<String>,
+}
+impl Case {
+    fn parse(line: &str) -> Case {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        assert_eq!(
+            fields.len(),
+            6,
+            "Expected 6 fields in golden line {:?}",
+            line
+        );
+        let optional = |field: &str| (field != "-").then(|| field.to_string());
+
+        Case {
+            rom: fields[0].to_string(),
+            quirks: fields[1].to_string(),
+            frames: fields[2].parse().expect("Invalid frame count"),
+            preset: optional(fields[3]).map(|preset| preset.parse().expect("Invalid preset")),
+            inputs: optional(fields[4]),
+            hash: optional(fields[5]),
+        }
+    }
+    fn to_line(&self, hash: &str) -> String {
+        let preset = self
+            .preset
+            .map_or("-".to_string(), |preset| preset.to_string());
+        format!(
+            "{:<13} {:<8} {:<3} {} {:<15} {}",
+            self.rom,
+            self.quirks,
+            self.frames,
+            preset,
+            self.inputs.as_deref().unwrap_or("-"),
+            hash
+        )
+    }
+
+    /** Hash of the framebuffer after running, an error if the ROM isn't checked in */
+    fn run(&self, roms: &Path) -> Result<String, String> {
+        let rom = fs::read(roms.join(&self.rom))
+            .map_err(|e| format!("{}: can't read the ROM from tests/roms: {}", self.rom, e))?;
+        let compatibility: Compatibility = self.quirks.parse().unwrap();
+        let inputs = match &self.inputs {
+            Some(inputs) => InputScript::parse(&fs::read_to_string(roms.join(inputs)).unwrap())
+                .expect("Invalid input script"),
+            None => InputScript::default(),
+        };
+
+        let mut emulator = Emulator::headless(compatibility);
+        emulator.set_seed(0);
+        emulator.load_rom(rom).unwrap();
+        if let Some(preset) = self.preset {
+            emulator.write_memory(0x1FF, vec![preset]).unwrap();
+        }
+        for _ in 0..self.frames * Emulator::CYCLES_PER_FRAME as u64 {
+            emulator.apply_inputs(&inputs);
+            emulator.run(1);
+        }
+
+        Ok(sha1_smol::Sha1::from(emulator.framebuffer().to_bytes())
+            .digest()
+            .to_string())
+    }
+}
+
+#[test]
+fn test_roms_match_golden_framebuffers() {
+    let roms = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/roms");
+    let golden_path = roms.join("golden.txt");
+    let golden = fs::read_to_string(&golden_path).unwrap();
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut lines = Vec::new();
+    let mut missing = Vec::new();
+    let mut failures = Vec::new();
+    for line in golden.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let case = Case::parse(line);
+        let hash = match case.run(&roms) {
+            Ok(hash) => hash,
+            Err(error) => {
+                missing.push(error);
+                lines.push(line.to_string());
+                continue;
+            }
+        };
+        match &case.hash {
+            Some(expected) if *expected == hash => {}
+            Some(expected) => failures.push(format!(
+                "{} ({} quirks): framebuffer hash {} instead of {}",
+                case.rom, case.quirks, hash, expected
+            )),
+            None => failures.push(format!(
+                "{} ({} quirks): no golden hash recorded, got {}",
+                case.rom, case.quirks, hash
+            )),
+        }
+        lines.push(case.to_line(&hash));
+    }
+
+    if update {
+        fs::write(&golden_path, lines.join("\n") + "\n").unwrap();
+    }
+    assert!(
+        missing.is_empty(),
+        "Test ROMs are missing:\n{}",
+        missing.join("\n")
+    );
+    if update {
+        return;
+    }
+    assert!(
+        failures.is_empty(),
+        "Test ROMs don't match tests/roms/golden.txt, check them by eye and rerun with UPDATE_GOLDEN=1 if the change is intended:\n{}",
+        failures.join("\n")
+    );
+}
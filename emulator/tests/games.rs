@@ -0,0 +1,174 @@
+//! Plays the games in `public/roms` with the scripted inputs in `tests/games` and checks the display at checkpoints
+#![cfg(not(target_arch = "wasm32"))]
+
+use chip8_emulator::input::InputScript;
+use chip8_emulator::{snapshot, Compatibility, Emulator, Framebuffer};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+/** Pixel size of the PNGs written on failure */
+const DIFF_SCALE: usize = 8;
+
+fn check_game(name: &str, compatibility: Compatibility) {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let games = manifest.join("tests/games");
+    let rom = fs::read(manifest.join(format!("../public/roms/{}.ch8", name))).unwrap();
+    let inputs = fs::read_to_string(games.join(format!("{}.inputs", name))).unwrap();
+    let inputs = InputScript::parse(&inputs).unwrap();
+    let golden_path = games.join(format!("{}.golden", name));
+    let golden = fs::read_to_string(&golden_path).unwrap();
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut emulator = Emulator::headless(compatibility);
+    emulator.set_seed(1);
+    emulator.load_rom(rom).unwrap();
+
+    let mut frame = 0;
+    let mut lines = Vec::new();
+    let mut failures = Vec::new();
+    for line in golden.lines() {
+        let Some((checkpoint, expected)) = line.split_once(' ').filter(|_| !line.starts_with('#'))
+        else {
+            lines.push(line.to_string());
+            continue;
+        };
+        let checkpoint: u64 = checkpoint.parse().expect("Invalid checkpoint frame");
+
+        while frame < checkpoint {
+            for _ in 0..Emulator::CYCLES_PER_FRAME {
+                emulator.apply_inputs(&inputs);
+                emulator.run(1);
+            }
+            frame += 1;
+        }
+
+        let framebuffer = emulator.framebuffer();
+        let hash = sha1_smol::Sha1::from(framebuffer.to_bytes())
+            .digest()
+            .to_string();
+        let expected_png = games.join(format!("expected/{}-{}.png", name, checkpoint));
+        if update {
+            fs::create_dir_all(expected_png.parent().unwrap()).unwrap();
+            fs::write(&expected_png, snapshot::to_png(framebuffer, 1)).unwrap();
+        } else if hash != expected {
+            failures.push(format!(
+                "Frame {}: hash {} instead of {}, see {}",
+                checkpoint,
+                hash,
+                expected,
+                write_diff(name, checkpoint, &expected_png, framebuffer).display()
+            ));
+        }
+        lines.push(format!("{} {}", checkpoint, hash));
+    }
+
+    if update {
+        fs::write(&golden_path, lines.join("\n") + "\n").unwrap();
+        return;
+    }
+    assert!(
+        failures.is_empty(),
+        "{} doesn't match tests/games/{}.golden, rerun with UPDATE_GOLDEN=1 if the change is intended:\n{}",
+        name,
+        name,
+        failures.join("\n")
+    );
+}
+
+/** Writes expected, actual and diff PNGs next to the test binaries and returns the directory */
+fn write_diff(name: &str, checkpoint: u64, expected_png: &Path, actual: &Framebuffer) -> PathBuf {
+    let directory = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("game-diffs");
+    fs::create_dir_all(&directory).unwrap();
+    let path = |kind: &str| directory.join(format!("{}-{}-{}.png", name, checkpoint, kind));
+
+    // Missing when the checkpoint was never recorded, which diffs against a blank display
+    let expected = fs::read(expected_png)
+        .map(|png| Framebuffer::from_bytes(&decode(&png)))
+        .unwrap_or_default();
+    fs::write(path("expected"), snapshot::to_png(&expected, DIFF_SCALE)).unwrap();
+    fs::write(path("actual"), snapshot::to_png(actual, DIFF_SCALE)).unwrap();
+    fs::write(path("diff"), diff_png(&expected, actual)).unwrap();
+
+    directory
+}
+
+fn decode(png: &[u8]) -> Vec<u8> {
+    let mut reader = png::Decoder::new(png).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+
+    pixels.iter().map(|&pixel| (pixel != 0) as u8).collect()
+}
+
+/** White where both have a pixel on, red where only the expected one does and green where only the actual one does */
+fn diff_png(expected: &Framebuffer, actual: &Framebuffer) -> Vec<u8> {
+    const WIDTH: usize = 64;
+    let height = expected.rows().len();
+    let colors: Vec<[u8; 3]> = (0..height)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .map(|(x, y)| match (expected.get(x, y), actual.get(x, y)) {
+            (false, false) => [0x00, 0x00, 0x00],
+            (true, false) => [0xFF, 0x40, 0x40],
+            (false, true) => [0x40, 0xFF, 0x40],
+            (true, true) => [0xFF, 0xFF, 0xFF],
+        })
+        .collect();
+
+    let mut rgb = Vec::with_capacity(colors.len() * 3 * DIFF_SCALE * DIFF_SCALE);
+    for row in colors.chunks(WIDTH) {
+        for _ in 0..DIFF_SCALE {
+            for color in row {
+                for _ in 0..DIFF_SCALE {
+                    rgb.extend_from_slice(color);
+                }
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(
+        &mut png,
+        (WIDTH * DIFF_SCALE) as u32,
+        (height * DIFF_SCALE) as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&rgb).unwrap();
+    writer.finish().unwrap();
+
+    png
+}
+
+#[test]
+fn test_airplane() {
+    check_game("airplane", Compatibility::New);
+}
+#[test]
+fn test_brix() {
+    check_game("brix", Compatibility::New);
+}
+#[test]
+fn test_cavern() {
+    check_game("cavern", Compatibility::New);
+}
+#[test]
+fn test_invaders() {
+    check_game("invaders", Compatibility::New);
+}
+#[test]
+fn test_pong1() {
+    check_game("pong1", Compatibility::New);
+}
+#[test]
+fn test_pong2() {
+    check_game("pong2", Compatibility::New);
+}
+#[test]
+fn test_tetris() {
+    check_game("tetris", Compatibility::New);
+}
+#[test]
+fn test_worm() {
+    check_game("worm", Compatibility::New);
+}
@@ -1,21 +1,22 @@
 import "./style.css";
 import * as wasm from "chip8-emulator";
 import { Emulator } from "chip8-emulator";
-import "./fasterInterval.js";
 
 const WIDTH = 64;
 const HEIGHT = 32;
 const SCALE = 10;
+// 500 instructions a second, run in batches so the wasm boundary is crossed less often
+const CYCLES_PER_BATCH = 5;
+const BATCH_INTERVAL = 10;
 
 const canvas = document.querySelector("canvas")!;
 canvas.style.width = WIDTH * SCALE + "px";
 canvas.style.height = HEIGHT * SCALE + "px";
 
 wasm.init();
-document.onkeydown = e => wasm.on_key_down(e.code);
-document.onkeyup = () => wasm.on_key_up();
-
 const emulator = Emulator.init(1);
+document.onkeydown = e => emulator.on_key_down(e.code);
+document.onkeyup = () => emulator.on_key_up();
 
 const selectedRom = document.getElementById("rom")! as HTMLSelectElement;
 selectedRom.onchange = async () => {
@@ -23,10 +24,17 @@ selectedRom.onchange = async () => {
 	await loadRom();
 };
 
-document.getElementById("reload")!.onclick = async () => await loadRom();
+// Shift+click resets like the VIP reset switch, keeping memory intact
+document.getElementById("reload")!.onclick = e => {
+	if (e.shiftKey) {
+		emulator.reset_soft();
+	} else {
+		emulator.reset_hard();
+	}
+};
 
 await loadRom();
-setInterval(cycle, 2);
+setInterval(run, BATCH_INTERVAL);
 draw();
 
 async function loadRom() {
@@ -37,9 +45,9 @@ async function loadRom() {
 	emulator.load_rom(new Uint8Array(data));
 }
 
-function cycle() {
+function run() {
 	if (selectedRom.value === "") return;
-	emulator.cycle();
+	emulator.run(CYCLES_PER_BATCH);
 }
 function draw() {
 	emulator.draw();